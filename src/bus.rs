@@ -0,0 +1,346 @@
+use crate::{
+    interrupts::{IE_ADDR, IF_ADDR, Interrupt},
+    utils,
+};
+
+/// Everything the CPU can see through its address pins.
+///
+/// Implementors only provide the raw `read`/`write` accesses. The CPU goes
+/// through `read_byte`/`write_byte`, which call `tick` once per access, so a
+/// bus can advance other hardware or record each M-cycle as it happens.
+pub trait Bus {
+    fn read(&mut self, addr: u16) -> u8;
+
+    fn write(&mut self, addr: u16, value: u8);
+
+    /// Called before every memory access made by the CPU, each one takes an
+    /// M-cycle (4 T-cycles).
+    fn tick(&mut self) {}
+
+    fn read_byte(&mut self, addr: u16) -> u8 {
+        self.tick();
+        self.read(addr)
+    }
+
+    fn write_byte(&mut self, addr: u16, value: u8) {
+        self.tick();
+        self.write(addr, value);
+    }
+
+    /// Reads a little-endian word, the high byte wraps around to 0x0000.
+    fn read_word(&mut self, addr: u16) -> u16 {
+        let low = self.read_byte(addr) as u16;
+        let high = self.read_byte(addr.wrapping_add(1)) as u16;
+
+        (high << 8) | low
+    }
+
+    /// Writes `value` little-endian, the high byte wraps around to 0x0000.
+    fn write_word(&mut self, addr: u16, value: u16) {
+        let (hi, lo) = utils::split_hi_lo(value);
+
+        self.write_byte(addr, lo);
+        self.write_byte(addr.wrapping_add(1), hi);
+    }
+
+    /// Performs the CGB double speed switch if KEY1 has it armed, called by
+    /// STOP. Returns false on machines without KEY1, where STOP just stops.
+    fn switch_speed(&mut self) -> bool {
+        false
+    }
+
+    /// Interrupts that are both requested and enabled.
+    fn pending_interrupts(&mut self) -> u8 {
+        self.read(IE_ADDR) & self.read(IF_ADDR) & 0x1F
+    }
+
+    fn is_requested(&mut self, interrupt: Interrupt) -> bool {
+        self.read(IF_ADDR) & interrupt.bit() != 0
+    }
+
+    fn clear_interrupt(&mut self, interrupt: Interrupt) {
+        let flags = self.read(IF_ADDR);
+        self.write(IF_ADDR, flags & !interrupt.bit());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bus;
+    use crate::{
+        cpu::Cpu,
+        interrupts::{IF_ADDR, Interrupt},
+    };
+
+    /// 64 KiB of plain RAM with no memory map or I/O side effects.
+    pub struct FlatRam {
+        pub memory: Box<[u8; 0x10000]>,
+        pub ticks: u32,
+    }
+
+    impl FlatRam {
+        pub fn new() -> Self {
+            FlatRam {
+                memory: Box::new([0; 0x10000]),
+                ticks: 0,
+            }
+        }
+    }
+
+    impl Bus for FlatRam {
+        fn read(&mut self, addr: u16) -> u8 {
+            self.memory[addr as usize]
+        }
+
+        fn write(&mut self, addr: u16, value: u8) {
+            self.memory[addr as usize] = value;
+        }
+
+        fn tick(&mut self) {
+            self.ticks += 1;
+        }
+    }
+
+    /// Wraps another bus and records every access the CPU makes.
+    struct LoggingBus<B: Bus> {
+        inner: B,
+        log: Vec<(u16, u8, bool)>,
+    }
+
+    impl<B: Bus> Bus for LoggingBus<B> {
+        fn read(&mut self, addr: u16) -> u8 {
+            let value = self.inner.read(addr);
+            self.log.push((addr, value, false));
+            value
+        }
+
+        fn write(&mut self, addr: u16, value: u8) {
+            self.log.push((addr, value, true));
+            self.inner.write(addr, value);
+        }
+    }
+
+    #[test]
+    fn test_cpu_runs_on_flat_ram() {
+        let mut cpu = Cpu::new();
+        let mut bus = FlatRam::new();
+        bus.memory[0x0000] = 0x3E; // LD A, 0x42
+        bus.memory[0x0001] = 0x42;
+        bus.memory[0x0002] = 0xEA; // LD [0x0010], A
+        bus.memory[0x0003] = 0x10;
+        bus.memory[0x0004] = 0x00;
+
+        cpu.step(&mut bus);
+        cpu.step(&mut bus);
+
+        // Writes to 0x0000-0x7FFF land in RAM instead of an MBC
+        assert_eq!(bus.memory[0x0010], 0x42);
+        assert_eq!(bus.ticks, 6);
+    }
+
+    #[test]
+    fn test_logging_bus_sees_every_access() {
+        let mut cpu = Cpu::new();
+        let mut bus = LoggingBus {
+            inner: FlatRam::new(),
+            log: Vec::new(),
+        };
+        cpu.registers.sp = 0xD000;
+        bus.inner.memory[0x0000] = 0xC5; // PUSH BC
+        cpu.registers.write_bc(0x1234);
+
+        cpu.step(&mut bus);
+
+        assert_eq!(
+            bus.log,
+            vec![
+                (0x0000, 0xC5, false),
+                (0xCFFF, 0x12, true),
+                (0xCFFE, 0x34, true)
+            ]
+        );
+    }
+
+    // M-cycle, address and whether it is a write
+    type Access = (u32, u16, bool);
+
+    /// Records the M-cycle, counted from 1, on which each access lands.
+    struct TimingBus {
+        inner: FlatRam,
+        log: Vec<Access>,
+    }
+
+    impl Bus for TimingBus {
+        fn read(&mut self, addr: u16) -> u8 {
+            self.log.push((self.inner.ticks, addr, false));
+            self.inner.read(addr)
+        }
+
+        fn write(&mut self, addr: u16, value: u8) {
+            self.log.push((self.inner.ticks, addr, true));
+            self.inner.write(addr, value);
+        }
+
+        fn tick(&mut self) {
+            self.inner.tick();
+        }
+    }
+
+    // The M-cycle each access of an instruction lands on, as measured by
+    // Blargg's mem_timing and listed in the Pan Docs, along with the total
+    // T-cycles checked by instr_timing
+    #[test]
+    fn test_access_timing() {
+        const R: bool = false;
+        const W: bool = true;
+        let cases: [(&str, &[u8], &[Access], u8); 18] = [
+            ("LD A, [HL]", &[0x7E], &[(1, 0x0100, R), (2, 0xC000, R)], 8),
+            ("LD [HL], A", &[0x77], &[(1, 0x0100, R), (2, 0xC000, W)], 8),
+            ("LD [HL+], A", &[0x22], &[(1, 0x0100, R), (2, 0xC000, W)], 8),
+            (
+                "INC [HL]",
+                &[0x34],
+                &[(1, 0x0100, R), (2, 0xC000, R), (3, 0xC000, W)],
+                12,
+            ),
+            (
+                "LD A, [a16]",
+                &[0xFA, 0x00, 0xC0],
+                &[
+                    (1, 0x0100, R),
+                    (2, 0x0101, R),
+                    (3, 0x0102, R),
+                    (4, 0xC000, R),
+                ],
+                16,
+            ),
+            (
+                "LD [a16], A",
+                &[0xEA, 0x00, 0xC0],
+                &[
+                    (1, 0x0100, R),
+                    (2, 0x0101, R),
+                    (3, 0x0102, R),
+                    (4, 0xC000, W),
+                ],
+                16,
+            ),
+            (
+                "LD [a16], SP",
+                &[0x08, 0x00, 0xC0],
+                &[
+                    (1, 0x0100, R),
+                    (2, 0x0101, R),
+                    (3, 0x0102, R),
+                    (4, 0xC000, W),
+                    (5, 0xC001, W),
+                ],
+                20,
+            ),
+            (
+                "LDH A, [a8]",
+                &[0xF0, 0x80],
+                &[(1, 0x0100, R), (2, 0x0101, R), (3, 0xFF80, R)],
+                12,
+            ),
+            (
+                "BIT 0, [HL]",
+                &[0xCB, 0x46],
+                &[(1, 0x0100, R), (2, 0x0101, R), (3, 0xC000, R)],
+                12,
+            ),
+            (
+                "RLC [HL]",
+                &[0xCB, 0x06],
+                &[
+                    (1, 0x0100, R),
+                    (2, 0x0101, R),
+                    (3, 0xC000, R),
+                    (4, 0xC000, W),
+                ],
+                16,
+            ),
+            (
+                "ADD SP, e8",
+                &[0xE8, 0x02],
+                &[(1, 0x0100, R), (2, 0x0101, R)],
+                16,
+            ),
+            (
+                "PUSH BC",
+                &[0xC5],
+                &[(1, 0x0100, R), (3, 0xCFFF, W), (4, 0xCFFE, W)],
+                16,
+            ),
+            (
+                "POP BC",
+                &[0xC1],
+                &[(1, 0x0100, R), (2, 0xD000, R), (3, 0xD001, R)],
+                12,
+            ),
+            (
+                "JP a16",
+                &[0xC3, 0x00, 0x20],
+                &[(1, 0x0100, R), (2, 0x0101, R), (3, 0x0102, R)],
+                16,
+            ),
+            (
+                "CALL a16",
+                &[0xCD, 0x00, 0x20],
+                &[
+                    (1, 0x0100, R),
+                    (2, 0x0101, R),
+                    (3, 0x0102, R),
+                    (5, 0xCFFF, W),
+                    (6, 0xCFFE, W),
+                ],
+                24,
+            ),
+            (
+                "RET",
+                &[0xC9],
+                &[(1, 0x0100, R), (2, 0xD000, R), (3, 0xD001, R)],
+                16,
+            ),
+            (
+                "RET NZ",
+                &[0xC0],
+                &[(1, 0x0100, R), (3, 0xD000, R), (4, 0xD001, R)],
+                20,
+            ),
+            (
+                "RST 0x38",
+                &[0xFF],
+                &[(1, 0x0100, R), (3, 0xCFFF, W), (4, 0xCFFE, W)],
+                16,
+            ),
+        ];
+
+        for (name, code, accesses, cycles) in cases {
+            let mut cpu = Cpu::new();
+            let mut bus = TimingBus {
+                inner: FlatRam::new(),
+                log: Vec::new(),
+            };
+            cpu.registers.pc = 0x0100;
+            cpu.registers.sp = 0xD000;
+            cpu.registers.write_hl(0xC000);
+            bus.inner.memory[0x0100..0x0100 + code.len()].copy_from_slice(code);
+
+            assert_eq!(cpu.step(&mut bus), cycles, "{name}");
+            assert_eq!(bus.log, accesses, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_clear_interrupt() {
+        let mut bus = FlatRam::new();
+        bus.memory[IF_ADDR as usize] = 0x05;
+
+        bus.clear_interrupt(Interrupt::Timer);
+
+        assert_eq!(bus.memory[IF_ADDR as usize], 0x01);
+        assert!(bus.is_requested(Interrupt::VBlank));
+        assert_eq!(bus.ticks, 0);
+    }
+}
@@ -1,102 +1,336 @@
 use crate::{
-    instructions::Instruction,
-    mmu::Mmu,
-    registers::{Reg, Reg16, Registers},
+    bus::Bus, instructions::Instruction, interrupts::Interrupt, registers::Registers, utils,
 };
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CpuState {
+    Running,
+    Halted,
+    Stopped,
+}
+
 pub struct Cpu {
-    pub cycles: u16,
     pub registers: Registers,
+    pub state: CpuState,
+    pub ime: bool,
+    // Set by EI, IME only turns on after the following instruction
+    pub ime_scheduled: bool,
+    // Set by HALT when IME=0 and an interrupt is already pending: the next
+    // opcode fetch does not increment PC, so its byte is read twice
+    pub halt_bug: bool,
 }
 
 impl Cpu {
     pub fn new() -> Self {
         Cpu {
-            cycles: 0,
             registers: Registers::new(),
+            state: CpuState::Running,
+            ime: false,
+            ime_scheduled: false,
+            halt_bug: false,
         }
     }
 
-    pub fn fetch_immediate_byte(&mut self, mmu: &mut Mmu) -> u8 {
-        let byte = mmu.read_byte(self.registers.pc);
-        self.registers.pc += 1;
+    pub fn fetch_immediate_byte(&mut self, bus: &mut dyn Bus) -> u8 {
+        let byte = bus.read_byte(self.registers.pc);
+        self.registers.pc = self.registers.pc.wrapping_add(1);
         byte
     }
 
-    pub fn fetch_immediate_word(&mut self, mmu: &mut Mmu) -> u16 {
-        let word = mmu.read_word(self.registers.pc);
-        self.registers.pc += 2;
+    pub fn fetch_immediate_word(&mut self, bus: &mut dyn Bus) -> u16 {
+        let word = bus.read_word(self.registers.pc);
+        self.registers.pc = self.registers.pc.wrapping_add(2);
         word
     }
 
-    pub fn step(&mut self, mmu: &mut Mmu) {
-        let byte = self.fetch_immediate_byte(mmu);
-        let inst = Instruction::from_byte(byte).expect("Unkown instruction!");
+    // The high byte goes out first, each write after its own SP decrement
+    pub fn push_word(&mut self, bus: &mut dyn Bus, value: u16) {
+        let (hi, lo) = utils::split_hi_lo(value);
 
-        let cycles = (inst.execute)(self, mmu);
+        self.registers.sp = self.registers.sp.wrapping_sub(1);
+        bus.write_byte(self.registers.sp, hi);
+        self.registers.sp = self.registers.sp.wrapping_sub(1);
+        bus.write_byte(self.registers.sp, lo);
     }
 
-    fn copy(&mut self, from: Reg, to: Reg) -> u8 {
-        self.registers.write_reg(to, self.registers.read_reg(from));
+    pub fn pop_word(&mut self, bus: &mut dyn Bus) -> u16 {
+        let value = bus.read_word(self.registers.sp);
+        self.registers.sp = self.registers.sp.wrapping_add(2);
 
-        4
+        value
     }
 
-    fn copy_to_address(&mut self, from: Reg, to: Reg16, mem_bus: &mut Mmu) -> u8 {
-        let addr = self.registers.read_reg16(to);
-        let value = self.registers.read_reg(from);
-        mem_bus.write_byte(addr, value);
+    /// Runs one instruction, interrupt dispatch or idle cycle and returns
+    /// how many T-cycles it took.
+    pub fn step<B: Bus>(&mut self, bus: &mut B) -> u8 {
+        match self.state {
+            CpuState::Halted if bus.pending_interrupts() == 0 => 4,
+            CpuState::Stopped if !bus.is_requested(Interrupt::Joypad) => 4,
+            _ => {
+                self.state = CpuState::Running;
 
-        8
+                match self.service_interrupt(bus) {
+                    Some(cycles) => cycles,
+                    None => self.execute_next(bus),
+                }
+            }
+        }
     }
 
-    fn load_byte(&mut self, to: Reg, mem_bus: &mut Mmu) -> u8 {
-        let value = mem_bus.read_byte(self.registers.pc);
-        self.registers.pc += 1;
-        self.registers.write_reg(to, value);
+    /// Jumps to the vector of the highest priority pending interrupt if IME is set.
+    fn service_interrupt(&mut self, bus: &mut dyn Bus) -> Option<u8> {
+        if !self.ime {
+            return None;
+        }
 
-        8
+        let interrupt = Interrupt::highest_priority(bus.pending_interrupts())?;
+
+        self.ime = false;
+        bus.clear_interrupt(interrupt);
+        // Two wait cycles before PC is pushed
+        bus.tick();
+        bus.tick();
+        self.push_word(bus, self.registers.pc);
+        self.registers.pc = interrupt.vector();
+
+        Some(20)
     }
 
-    fn load_from_address(&mut self, reg_addr: Reg16, to: Reg, mem_bus: &mut Mmu) -> u8 {
-        let addr = self.registers.read_reg16(reg_addr);
-        let value = mem_bus.read_byte(addr);
-        self.registers.write_reg(to, value);
+    fn execute_next(&mut self, bus: &mut dyn Bus) -> u8 {
+        let enable_ime = self.ime_scheduled;
+
+        let byte = if self.halt_bug {
+            self.halt_bug = false;
+            bus.read_byte(self.registers.pc)
+        } else {
+            self.fetch_immediate_byte(bus)
+        };
+        let inst = match byte {
+            0xCB => {
+                let byte = self.fetch_immediate_byte(bus);
+                Instruction::from_cb_byte(byte).expect("Unknown CB instruction!")
+            }
+            _ => Instruction::from_byte(byte).expect("Unkown instruction!"),
+        };
 
-        8
+        let cycles = (inst.execute)(self, bus);
+
+        // EI takes effect after the instruction following it, unless DI cancelled it
+        if enable_ime && self.ime_scheduled {
+            self.ime = true;
+            self.ime_scheduled = false;
+        }
+
+        cycles
     }
+}
 
-    fn load_from_loaded_address(&mut self, to: Reg, mem_bus: &mut Mmu) -> u8 {
-        let addr = mem_bus.read_word(self.registers.pc);
-        self.registers.pc += 2;
-        let value = mem_bus.read_byte(addr);
-        self.registers.write_reg(to, value);
+impl Default for Cpu {
+    fn default() -> Self {
+        Cpu::new()
+    }
+}
 
-        12
+#[cfg(test)]
+mod tests {
+    use super::{Cpu, CpuState};
+    use crate::{interrupts::Interrupt, mmu::Mmu};
+
+    #[test]
+    fn test_stack_wraps_around_address_space() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.sp = 0x0001;
+
+        cpu.push_word(&mut mmu, 0x1234);
+        assert_eq!(cpu.registers.sp, 0xFFFF);
+        assert_eq!(mmu.ie, 0x34);
+
+        mmu.poke(0xFFFF, 0x78);
+        assert_eq!(cpu.pop_word(&mut mmu), 0xFF78);
+        assert_eq!(cpu.registers.sp, 0x0001);
+    }
+
+    #[test]
+    fn test_step_dispatches_cb_prefix() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC000;
+        cpu.registers.a = 0x01;
+        mmu.poke(0xC000, 0xCB);
+        mmu.poke(0xC001, 0x37); // SWAP A
+
+        cpu.step(&mut mmu);
+
+        assert_eq!(cpu.registers.pc, 0xC002);
+        assert_eq!(cpu.registers.a, 0x10);
     }
 
-    fn load_word(&mut self, reg: Reg16, mem_bus: &mut Mmu) -> u8 {
-        let value = mem_bus.read_word(self.registers.pc);
-        self.registers.pc += 2;
-        self.registers.write_reg16(reg, value);
+    #[test]
+    fn test_ei_is_delayed_by_one_instruction() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC000;
+        cpu.registers.sp = 0xDFFE;
+        mmu.poke(0xC000, 0xFB); // EI
+        mmu.poke(0xC001, 0x00); // NOP
+        mmu.ie = 0x01;
+        mmu.request_interrupt(Interrupt::VBlank);
+
+        cpu.step(&mut mmu);
+        assert!(!cpu.ime);
+        assert_eq!(cpu.registers.pc, 0xC001);
+
+        cpu.step(&mut mmu);
+        assert!(cpu.ime);
+        assert_eq!(cpu.registers.pc, 0xC002);
 
-        12
+        cpu.step(&mut mmu);
+        assert_eq!(cpu.registers.pc, 0x0040);
     }
 
-    fn copy_to_loaded_address(&mut self, from: Reg, mem_bus: &mut Mmu) -> u8 {
-        let addr = mem_bus.read_word(self.registers.pc);
-        self.registers.pc += 2;
-        let value = self.registers.read_reg(from);
-        mem_bus.write_byte(addr, value);
+    #[test]
+    fn test_service_interrupt_by_priority() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.ime = true;
+        cpu.registers.pc = 0x1234;
+        cpu.registers.sp = 0xDFFE;
+        mmu.ie = 0x1F;
+        mmu.request_interrupt(Interrupt::Joypad);
+        mmu.request_interrupt(Interrupt::Timer);
 
-        12
+        let cycles = cpu.service_interrupt(&mut mmu);
+
+        assert_eq!(cycles, Some(20));
+        assert!(!cpu.ime);
+        assert_eq!(cpu.registers.pc, 0x0050);
+        assert_eq!(cpu.pop_word(&mut mmu), 0x1234);
+        assert_eq!(mmu.int_flag, Interrupt::Joypad.bit());
     }
 
-    fn load_to_address(&mut self, reg_addr: Reg16, mem_bus: &mut Mmu) -> u8 {
-        let value = mem_bus.read_byte(self.registers.pc);
-        self.registers.pc += 1;
-        let addr = self.registers.read_reg16(reg_addr);
-        mem_bus.write_byte(addr, value);
+    #[test]
+    fn test_service_interrupt_requires_ime_and_ie() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        mmu.ie = 0x01;
+        mmu.request_interrupt(Interrupt::VBlank);
+
+        assert_eq!(cpu.service_interrupt(&mut mmu), None);
+
+        cpu.ime = true;
+        mmu.ie = 0x00;
+        assert_eq!(cpu.service_interrupt(&mut mmu), None);
+    }
+
+    #[test]
+    fn test_halt_idles_until_interrupt_pending() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC000;
+        cpu.registers.sp = 0xDFFE;
+        cpu.ime = true;
+        mmu.ie = 0x04;
+        mmu.poke(0xC000, 0x76); // HALT
+
+        cpu.step(&mut mmu);
+        cpu.step(&mut mmu);
+        assert_eq!(cpu.state, CpuState::Halted);
+        assert_eq!(cpu.registers.pc, 0xC001);
+
+        mmu.request_interrupt(Interrupt::Timer);
+        cpu.step(&mut mmu);
+        assert_eq!(cpu.state, CpuState::Running);
+        assert_eq!(cpu.registers.pc, 0x0050);
+        assert_eq!(cpu.pop_word(&mut mmu), 0xC001);
+    }
+
+    #[test]
+    fn test_halt_wakes_without_ime() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC000;
+        mmu.ie = 0x01;
+        mmu.poke(0xC000, 0x76); // HALT
+        mmu.poke(0xC001, 0x80); // ADD A, B
+
+        cpu.step(&mut mmu);
+        mmu.request_interrupt(Interrupt::VBlank);
+        cpu.step(&mut mmu);
+
+        assert_eq!(cpu.state, CpuState::Running);
+        assert_eq!(cpu.registers.pc, 0xC002);
+    }
+
+    #[test]
+    fn test_halt_bug_repeats_next_byte() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC000;
+        cpu.registers.b = 1;
+        mmu.ie = 0x01;
+        mmu.request_interrupt(Interrupt::VBlank);
+        mmu.poke(0xC000, 0x76); // HALT
+        mmu.poke(0xC001, 0x80); // ADD A, B
+
+        cpu.step(&mut mmu);
+        assert_eq!(cpu.state, CpuState::Running);
+
+        cpu.step(&mut mmu);
+        cpu.step(&mut mmu);
+
+        assert_eq!(cpu.registers.a, 2);
+        assert_eq!(cpu.registers.pc, 0xC002);
+    }
+
+    #[test]
+    fn test_stop_resets_div_and_waits_for_joypad() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC000;
+        mmu.poke(0xFF04, 0xAB);
+        mmu.poke(0xC000, 0x10); // STOP
+        mmu.poke(0xC001, 0x00);
+
+        cpu.step(&mut mmu);
+        cpu.step(&mut mmu);
+        assert_eq!(cpu.state, CpuState::Stopped);
+        assert_eq!(cpu.registers.pc, 0xC002);
+        assert_eq!(mmu.peek(0xFF04), 0);
+
+        mmu.request_interrupt(Interrupt::Joypad);
+        cpu.step(&mut mmu);
+        assert_eq!(cpu.state, CpuState::Running);
+        assert_eq!(cpu.registers.pc, 0xC003);
+    }
+
+    #[test]
+    fn test_stop_switches_speed_when_armed() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        mmu.cgb_mode = true;
+        cpu.registers.pc = 0xC000;
+        mmu.poke(0xFF4D, 0x01);
+        mmu.poke(0xC000, 0x10); // STOP
+        mmu.poke(0xC001, 0x00);
+
+        cpu.step(&mut mmu);
+
+        assert_eq!(cpu.state, CpuState::Running);
+        assert_eq!(mmu.peek(0xFF4D), 0xFE);
+    }
+
+    #[test]
+    fn test_stop_ignores_key1_on_dmg() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC000;
+        mmu.poke(0xFF4D, 0x01);
+        mmu.poke(0xC000, 0x10); // STOP
+        mmu.poke(0xC001, 0x00);
+
+        cpu.step(&mut mmu);
 
-        16
+        assert_eq!(cpu.state, CpuState::Stopped);
+        assert_eq!(mmu.peek(0xFF4D), 0xFF);
     }
 }
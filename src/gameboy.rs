@@ -0,0 +1,418 @@
+use std::io;
+
+use crate::{
+    cpu::Cpu,
+    mbc::rtc::{Clock, SystemClock},
+    mmu::Mmu,
+    ppu::{BGP_ADDR, FRAME_SIZE, LCDC_ADDR, Ppu, Renderer},
+    rom::{Cartridge, CartridgeError, Header},
+    save::SaveFile,
+    timer::Timer,
+};
+
+/// T-cycles from the start of one frame to the next: 154 lines of 456 dots.
+pub const CYCLES_PER_FRAME: u64 = 70224;
+
+pub struct GameBoy {
+    pub cpu: Cpu,
+    pub mmu: Mmu,
+    // T-cycles elapsed since power on
+    pub cycles: u64,
+    pub header: Header,
+    save_file: Option<SaveFile>,
+}
+
+impl GameBoy {
+    pub fn new(cartridge: Cartridge) -> Result<Self, CartridgeError> {
+        GameBoy::with_renderer(cartridge, Renderer::Scanline)
+    }
+
+    /// Like `new`, drawing the screen with the given PPU backend.
+    pub fn with_renderer(cartridge: Cartridge, renderer: Renderer) -> Result<Self, CartridgeError> {
+        GameBoy::build(cartridge, renderer, Box::new(SystemClock))
+    }
+
+    /// Like `new`, with the time source read by a cartridge real-time clock.
+    pub fn with_clock(cartridge: Cartridge, clock: Box<dyn Clock>) -> Result<Self, CartridgeError> {
+        GameBoy::build(cartridge, Renderer::Scanline, clock)
+    }
+
+    fn build(
+        cartridge: Cartridge,
+        renderer: Renderer,
+        clock: Box<dyn Clock>,
+    ) -> Result<Self, CartridgeError> {
+        let header = cartridge.header.clone();
+        let mut mmu = Mmu::new();
+        mmu.load_cartridge_with_clock(cartridge, clock)?;
+        mmu.ppu = Ppu::with_renderer(renderer);
+        // Internal divider value the DMG boot ROM leaves behind
+        mmu.timer = Timer::with_divider(0xABCC);
+        mmu.poke(LCDC_ADDR, 0x91);
+        mmu.poke(BGP_ADDR, 0xFC);
+
+        let mut cpu = Cpu::new();
+        // Register state the DMG boot ROM leaves behind when it jumps to the cartridge
+        cpu.registers.write_af(0x01B0);
+        cpu.registers.write_bc(0x0013);
+        cpu.registers.write_de(0x00D8);
+        cpu.registers.write_hl(0x014D);
+        cpu.registers.sp = 0xFFFE;
+        cpu.registers.pc = 0x0100;
+
+        Ok(GameBoy {
+            cpu,
+            mmu,
+            cycles: 0,
+            header,
+            save_file: None,
+        })
+    }
+
+    /// Advances the timer and other hardware on every memory access the CPU
+    /// makes instead of once per instruction. Slower, but needed by code that
+    /// relies on when within an instruction a read or write lands.
+    pub fn set_cycle_accurate(&mut self, enabled: bool) {
+        self.mmu.cycle_accurate = enabled;
+    }
+
+    /// Loads cartridge RAM from `save_file` and keeps it in sync from then on.
+    /// Ignored for cartridges without a battery.
+    pub fn attach_save_file(&mut self, save_file: SaveFile) -> io::Result<()> {
+        if !self.header.cartridge_type.battery {
+            return Ok(());
+        }
+
+        if let (Some(data), Some(mbc)) = (save_file.read()?, &mut self.mmu.mbc) {
+            mbc.load_save_data(&data);
+        }
+
+        self.mmu.cartridge_ram_dirty = false;
+        self.save_file = Some(save_file);
+
+        Ok(())
+    }
+
+    /// Writes cartridge RAM to the save file if it changed since the last flush.
+    pub fn flush_save(&mut self) -> io::Result<()> {
+        let (Some(save_file), Some(mbc)) = (&mut self.save_file, &mut self.mmu.mbc) else {
+            return Ok(());
+        };
+
+        if !self.mmu.cartridge_ram_dirty {
+            return Ok(());
+        }
+
+        save_file.write(&mbc.save_data())?;
+        self.mmu.cartridge_ram_dirty = false;
+
+        Ok(())
+    }
+
+    /// Runs a single CPU step and clocks the rest of the hardware by the same
+    /// amount. Returns the T-cycles it took.
+    pub fn step(&mut self) -> u8 {
+        let cycles = self.cpu.step(&mut self.mmu);
+        // In cycle-accurate mode accesses have already advanced the hardware,
+        // only the internal cycles that followed them are left
+        let ticked = self.mmu.take_ticked_cycles();
+        debug_assert!(ticked <= cycles, "accesses took longer than the step");
+        self.mmu.advance(cycles - ticked);
+        self.cycles += cycles as u64;
+
+        if self.mmu.cartridge_ram_dirty
+            && self.save_file.as_ref().is_some_and(SaveFile::is_flush_due)
+            && let Err(err) = self.flush_save()
+        {
+            log::error!("failed to write save file: {err}");
+        }
+
+        cycles
+    }
+
+    /// Runs until at least `cycles` T-cycles have elapsed. Instructions are
+    /// never split, so this can overshoot by a few cycles; returns the
+    /// number actually run.
+    pub fn run_cycles(&mut self, cycles: u64) -> u64 {
+        let start = self.cycles;
+        let target = start + cycles;
+
+        while self.cycles < target {
+            self.step();
+        }
+
+        self.cycles - start
+    }
+
+    /// Runs up to the next frame boundary. Overshoot from the previous call
+    /// is taken out of this frame so frames stay aligned to the clock.
+    pub fn run_frame(&mut self) -> u64 {
+        self.run_cycles(CYCLES_PER_FRAME - self.cycles % CYCLES_PER_FRAME)
+    }
+
+    /// The last frame the PPU finished, see `Ppu::frame`.
+    pub fn frame(&self) -> &[u8; FRAME_SIZE] {
+        self.mmu.ppu.frame()
+    }
+
+    /// Registers a callback invoked with `true`/`false` whenever the
+    /// cartridge turns its rumble motor on or off.
+    pub fn on_rumble(&mut self, callback: impl FnMut(bool) + 'static) {
+        if let Some(mbc) = &mut self.mmu.mbc {
+            mbc.set_rumble_callback(Box::new(callback));
+        }
+    }
+}
+
+impl Drop for GameBoy {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush_save() {
+            log::error!("failed to write save file: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CYCLES_PER_FRAME, GameBoy};
+    use crate::{
+        mbc::rtc::tests::FakeClock,
+        ppu::{FRAME_SIZE, SCREEN_HEIGHT, SCREEN_WIDTH, tests::RENDERERS},
+        rom::{Cartridge, tests::build_rom},
+        save::{
+            SaveFile,
+            tests::{backdate_flush, temp_dir},
+        },
+        timer::Timer,
+    };
+    use std::{cell::RefCell, fs, rc::Rc, time::Duration};
+
+    // LD A, 0x0A; LD [0x0000], A; LD A, 0x42; LD [0xA000], A
+    const WRITE_RAM: [u8; 10] = [0x3E, 0x0A, 0xEA, 0x00, 0x00, 0x3E, 0x42, 0xEA, 0x00, 0xA0];
+
+    fn battery_cart() -> Cartridge {
+        let mut rom = build_rom(0x03, 0x01, 0x02);
+        rom[0x100..0x10A].copy_from_slice(&WRITE_RAM);
+        Cartridge::from_bytes(rom).unwrap()
+    }
+
+    #[test]
+    fn test_run_frame_advances_clock_and_timer() {
+        let mut rom = build_rom(0x00, 0x00, 0x00);
+        // JR -2
+        rom[0x100..0x102].copy_from_slice(&[0x18, 0xFE]);
+        let mut gb = GameBoy::new(Cartridge::from_bytes(rom).unwrap()).unwrap();
+
+        assert_eq!(gb.run_frame(), CYCLES_PER_FRAME);
+        assert_eq!(gb.cycles, CYCLES_PER_FRAME);
+        assert_eq!(gb.mmu.peek(0xFF04), 0xBE);
+
+        gb.run_cycles(100);
+        assert_eq!(gb.cycles, CYCLES_PER_FRAME + 108);
+
+        gb.run_frame();
+        assert_eq!(gb.cycles, 2 * CYCLES_PER_FRAME);
+    }
+
+    #[test]
+    fn test_on_rumble_observes_motor() {
+        let mut rom = build_rom(0x1C, 0x01, 0x00);
+        // LD A, 0x08; LD [0x4000], A
+        rom[0x100..0x105].copy_from_slice(&[0x3E, 0x08, 0xEA, 0x00, 0x40]);
+        let mut gb = GameBoy::new(Cartridge::from_bytes(rom).unwrap()).unwrap();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let sink = events.clone();
+
+        gb.on_rumble(move |on| sink.borrow_mut().push(on));
+        gb.step();
+        gb.step();
+
+        assert_eq!(*events.borrow(), vec![true]);
+    }
+
+    #[test]
+    fn test_with_clock_drives_cartridge_rtc() {
+        let clock = FakeClock::new(0);
+        let cartridge = Cartridge::from_bytes(build_rom(0x10, 0x01, 0x02)).unwrap();
+        let mut gb = GameBoy::with_clock(cartridge, Box::new(clock.clone())).unwrap();
+        gb.mmu.poke(0x0000, 0x0A);
+
+        clock.advance(2 * 86400 + 3 * 3600 + 4 * 60 + 5);
+        gb.mmu.poke(0x6000, 0x00);
+        gb.mmu.poke(0x6000, 0x01);
+
+        // Seconds, minutes, hours and the low byte of the day counter
+        for (register, expected) in [(0x08, 5), (0x09, 4), (0x0A, 3), (0x0B, 2)] {
+            gb.mmu.poke(0x4000, register);
+            assert_eq!(gb.mmu.peek(0xA000), expected);
+        }
+    }
+
+    #[test]
+    fn test_save_loaded_on_attach() {
+        let dir = temp_dir("attach");
+        let mut save = vec![0; 0x2000];
+        save[0x10] = 0x99;
+        fs::write(dir.join("game.sav"), &save).unwrap();
+        let mut gb = GameBoy::new(battery_cart()).unwrap();
+
+        gb.attach_save_file(SaveFile::new(dir.join("game.sav")))
+            .unwrap();
+        gb.mmu.poke(0x0000, 0x0A);
+
+        assert_eq!(gb.mmu.peek(0xA010), 0x99);
+    }
+
+    #[test]
+    fn test_save_flushed_on_drop_only_when_dirty() {
+        let dir = temp_dir("drop");
+        let path = dir.join("game.sav");
+
+        let mut gb = GameBoy::new(battery_cart()).unwrap();
+        gb.attach_save_file(SaveFile::new(&path)).unwrap();
+        drop(gb);
+        assert!(!path.exists());
+
+        let mut gb = GameBoy::new(battery_cart()).unwrap();
+        gb.attach_save_file(SaveFile::new(&path)).unwrap();
+        for _ in 0..4 {
+            gb.step();
+        }
+        drop(gb);
+        assert_eq!(fs::read(&path).unwrap()[0], 0x42);
+    }
+
+    #[test]
+    fn test_save_flushed_periodically() {
+        let dir = temp_dir("periodic");
+        let path = dir.join("game.sav");
+        let mut gb = GameBoy::new(battery_cart()).unwrap();
+        let save = SaveFile::new(&path).with_flush_interval(Duration::ZERO);
+        gb.attach_save_file(save).unwrap();
+
+        for _ in 0..4 {
+            gb.step();
+        }
+
+        assert_eq!(fs::read(&path).unwrap()[0], 0x42);
+        assert!(!gb.mmu.cartridge_ram_dirty);
+    }
+
+    #[test]
+    fn test_failed_flush_not_retried_until_next_interval() {
+        let dir = temp_dir("failed-flush");
+        let path = dir.join("missing").join("game.sav");
+        let mut gb = GameBoy::new(battery_cart()).unwrap();
+        let mut save = SaveFile::new(&path);
+        backdate_flush(&mut save);
+        gb.attach_save_file(save).unwrap();
+
+        // The RAM write makes the flush due, and it fails on the missing directory
+        for _ in 0..4 {
+            gb.step();
+        }
+        assert!(gb.mmu.cartridge_ram_dirty);
+
+        // A retry would now succeed, but the next one waits for the interval
+        fs::create_dir(dir.join("missing")).unwrap();
+        for _ in 0..100 {
+            gb.step();
+        }
+        assert!(!path.exists());
+        assert!(gb.mmu.cartridge_ram_dirty);
+    }
+
+    #[test]
+    fn test_no_save_without_battery() {
+        let dir = temp_dir("no-battery");
+        let path = dir.join("game.sav");
+        let mut rom = build_rom(0x02, 0x01, 0x02);
+        rom[0x100..0x10A].copy_from_slice(&WRITE_RAM);
+        let mut gb = GameBoy::new(Cartridge::from_bytes(rom).unwrap()).unwrap();
+        gb.attach_save_file(SaveFile::new(&path)).unwrap();
+
+        for _ in 0..4 {
+            gb.step();
+        }
+        drop(gb);
+
+        assert!(!path.exists());
+    }
+
+    /// Reads a binary PGM as DMG shades, white being shade 0.
+    fn read_pgm(path: &str) -> Vec<u8> {
+        let data = fs::read(path).unwrap();
+        let header = format!("P5\n{SCREEN_WIDTH} {SCREEN_HEIGHT}\n255\n");
+        assert!(
+            data.starts_with(header.as_bytes()),
+            "{path}: unexpected header"
+        );
+
+        data[header.len()..]
+            .iter()
+            .map(|&grey| (255 - grey) / 85)
+            .collect()
+    }
+
+    // roms/dmg-acid2.pgm is reference-dmg.png from the dmg-acid2 repository,
+    // converted with `convert reference-dmg.png -depth 8 dmg-acid2.pgm`. It
+    // only uses the greys 0xFF, 0xAA, 0x55 and 0x00.
+    #[test]
+    #[ignore = "needs dmg-acid2.gb and dmg-acid2.pgm in roms/"]
+    fn test_dmg_acid2() {
+        let expected = read_pgm("roms/dmg-acid2.pgm");
+        assert_eq!(expected.len(), FRAME_SIZE);
+
+        for renderer in RENDERERS {
+            let cartridge = Cartridge::load("roms/dmg-acid2.gb").unwrap();
+            let mut gb = GameBoy::with_renderer(cartridge, renderer).unwrap();
+
+            // The test signals it is done by executing LD B, B
+            while gb.mmu.peek(gb.cpu.registers.pc) != 0x40 {
+                assert!(
+                    gb.cycles < 60 * CYCLES_PER_FRAME,
+                    "{renderer:?}: LD B, B never reached"
+                );
+                gb.step();
+            }
+            // Let the finished picture reach the front buffer
+            gb.run_frame();
+            gb.run_frame();
+
+            assert!(gb.frame()[..] == expected[..], "{renderer:?}");
+        }
+    }
+
+    #[test]
+    fn test_cycle_accurate_write_sees_timer_mid_instruction() {
+        let mut rom = build_rom(0x00, 0x00, 0x00);
+        // LD HL, 0xFF05; LD A, 0x05; LDH [0x07], A; NOP; INC [HL]
+        rom[0x100..0x10A]
+            .copy_from_slice(&[0x21, 0x05, 0xFF, 0x3E, 0x05, 0xE0, 0x07, 0x00, 0x34, 0x00]);
+        let mut gb = GameBoy::new(Cartridge::from_bytes(rom).unwrap()).unwrap();
+        gb.mmu.timer = Timer::new();
+        gb.set_cycle_accurate(true);
+
+        for _ in 0..4 {
+            gb.step();
+        }
+        assert_eq!(gb.cycles, 36);
+        // TIMA is bumped on cycle 48, between INC [HL] reading 0 and writing
+        // back 1, so the write wins over the timer increment
+        gb.step();
+
+        assert_eq!(gb.cycles, 48);
+        assert_eq!(gb.mmu.peek(0xFF05), 1);
+    }
+
+    #[test]
+    fn test_cycle_accurate_stop_counts_both_reads() {
+        let mut rom = build_rom(0x00, 0x00, 0x00);
+        rom[0x100..0x102].copy_from_slice(&[0x10, 0x00]); // STOP
+        let mut gb = GameBoy::new(Cartridge::from_bytes(rom).unwrap()).unwrap();
+        gb.set_cycle_accurate(true);
+
+        assert_eq!(gb.step(), 8);
+        assert_eq!(gb.cycles, 8);
+    }
+}
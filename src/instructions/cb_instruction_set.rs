@@ -0,0 +1,263 @@
+use crate::{
+    instructions::{Instruction, functions::*},
+    registers::Reg,
+};
+
+pub static CB_INSTRUCTIONS: [Instruction; 256] = [
+    Instruction::new(0x00, "RLC B", |cpu, _| cb_reg(cpu, Reg::B, rlc)),
+    Instruction::new(0x01, "RLC C", |cpu, _| cb_reg(cpu, Reg::C, rlc)),
+    Instruction::new(0x02, "RLC D", |cpu, _| cb_reg(cpu, Reg::D, rlc)),
+    Instruction::new(0x03, "RLC E", |cpu, _| cb_reg(cpu, Reg::E, rlc)),
+    Instruction::new(0x04, "RLC H", |cpu, _| cb_reg(cpu, Reg::H, rlc)),
+    Instruction::new(0x05, "RLC L", |cpu, _| cb_reg(cpu, Reg::L, rlc)),
+    Instruction::new(0x06, "RLC [HL]", |cpu, bus| cb_addr_hl(cpu, bus, rlc)),
+    Instruction::new(0x07, "RLC A", |cpu, _| cb_reg(cpu, Reg::A, rlc)),
+    Instruction::new(0x08, "RRC B", |cpu, _| cb_reg(cpu, Reg::B, rrc)),
+    Instruction::new(0x09, "RRC C", |cpu, _| cb_reg(cpu, Reg::C, rrc)),
+    Instruction::new(0x0A, "RRC D", |cpu, _| cb_reg(cpu, Reg::D, rrc)),
+    Instruction::new(0x0B, "RRC E", |cpu, _| cb_reg(cpu, Reg::E, rrc)),
+    Instruction::new(0x0C, "RRC H", |cpu, _| cb_reg(cpu, Reg::H, rrc)),
+    Instruction::new(0x0D, "RRC L", |cpu, _| cb_reg(cpu, Reg::L, rrc)),
+    Instruction::new(0x0E, "RRC [HL]", |cpu, bus| cb_addr_hl(cpu, bus, rrc)),
+    Instruction::new(0x0F, "RRC A", |cpu, _| cb_reg(cpu, Reg::A, rrc)),
+    Instruction::new(0x10, "RL B", |cpu, _| cb_reg(cpu, Reg::B, rl)),
+    Instruction::new(0x11, "RL C", |cpu, _| cb_reg(cpu, Reg::C, rl)),
+    Instruction::new(0x12, "RL D", |cpu, _| cb_reg(cpu, Reg::D, rl)),
+    Instruction::new(0x13, "RL E", |cpu, _| cb_reg(cpu, Reg::E, rl)),
+    Instruction::new(0x14, "RL H", |cpu, _| cb_reg(cpu, Reg::H, rl)),
+    Instruction::new(0x15, "RL L", |cpu, _| cb_reg(cpu, Reg::L, rl)),
+    Instruction::new(0x16, "RL [HL]", |cpu, bus| cb_addr_hl(cpu, bus, rl)),
+    Instruction::new(0x17, "RL A", |cpu, _| cb_reg(cpu, Reg::A, rl)),
+    Instruction::new(0x18, "RR B", |cpu, _| cb_reg(cpu, Reg::B, rr)),
+    Instruction::new(0x19, "RR C", |cpu, _| cb_reg(cpu, Reg::C, rr)),
+    Instruction::new(0x1A, "RR D", |cpu, _| cb_reg(cpu, Reg::D, rr)),
+    Instruction::new(0x1B, "RR E", |cpu, _| cb_reg(cpu, Reg::E, rr)),
+    Instruction::new(0x1C, "RR H", |cpu, _| cb_reg(cpu, Reg::H, rr)),
+    Instruction::new(0x1D, "RR L", |cpu, _| cb_reg(cpu, Reg::L, rr)),
+    Instruction::new(0x1E, "RR [HL]", |cpu, bus| cb_addr_hl(cpu, bus, rr)),
+    Instruction::new(0x1F, "RR A", |cpu, _| cb_reg(cpu, Reg::A, rr)),
+    Instruction::new(0x20, "SLA B", |cpu, _| cb_reg(cpu, Reg::B, sla)),
+    Instruction::new(0x21, "SLA C", |cpu, _| cb_reg(cpu, Reg::C, sla)),
+    Instruction::new(0x22, "SLA D", |cpu, _| cb_reg(cpu, Reg::D, sla)),
+    Instruction::new(0x23, "SLA E", |cpu, _| cb_reg(cpu, Reg::E, sla)),
+    Instruction::new(0x24, "SLA H", |cpu, _| cb_reg(cpu, Reg::H, sla)),
+    Instruction::new(0x25, "SLA L", |cpu, _| cb_reg(cpu, Reg::L, sla)),
+    Instruction::new(0x26, "SLA [HL]", |cpu, bus| cb_addr_hl(cpu, bus, sla)),
+    Instruction::new(0x27, "SLA A", |cpu, _| cb_reg(cpu, Reg::A, sla)),
+    Instruction::new(0x28, "SRA B", |cpu, _| cb_reg(cpu, Reg::B, sra)),
+    Instruction::new(0x29, "SRA C", |cpu, _| cb_reg(cpu, Reg::C, sra)),
+    Instruction::new(0x2A, "SRA D", |cpu, _| cb_reg(cpu, Reg::D, sra)),
+    Instruction::new(0x2B, "SRA E", |cpu, _| cb_reg(cpu, Reg::E, sra)),
+    Instruction::new(0x2C, "SRA H", |cpu, _| cb_reg(cpu, Reg::H, sra)),
+    Instruction::new(0x2D, "SRA L", |cpu, _| cb_reg(cpu, Reg::L, sra)),
+    Instruction::new(0x2E, "SRA [HL]", |cpu, bus| cb_addr_hl(cpu, bus, sra)),
+    Instruction::new(0x2F, "SRA A", |cpu, _| cb_reg(cpu, Reg::A, sra)),
+    Instruction::new(0x30, "SWAP B", |cpu, _| cb_reg(cpu, Reg::B, swap)),
+    Instruction::new(0x31, "SWAP C", |cpu, _| cb_reg(cpu, Reg::C, swap)),
+    Instruction::new(0x32, "SWAP D", |cpu, _| cb_reg(cpu, Reg::D, swap)),
+    Instruction::new(0x33, "SWAP E", |cpu, _| cb_reg(cpu, Reg::E, swap)),
+    Instruction::new(0x34, "SWAP H", |cpu, _| cb_reg(cpu, Reg::H, swap)),
+    Instruction::new(0x35, "SWAP L", |cpu, _| cb_reg(cpu, Reg::L, swap)),
+    Instruction::new(0x36, "SWAP [HL]", |cpu, bus| cb_addr_hl(cpu, bus, swap)),
+    Instruction::new(0x37, "SWAP A", |cpu, _| cb_reg(cpu, Reg::A, swap)),
+    Instruction::new(0x38, "SRL B", |cpu, _| cb_reg(cpu, Reg::B, srl)),
+    Instruction::new(0x39, "SRL C", |cpu, _| cb_reg(cpu, Reg::C, srl)),
+    Instruction::new(0x3A, "SRL D", |cpu, _| cb_reg(cpu, Reg::D, srl)),
+    Instruction::new(0x3B, "SRL E", |cpu, _| cb_reg(cpu, Reg::E, srl)),
+    Instruction::new(0x3C, "SRL H", |cpu, _| cb_reg(cpu, Reg::H, srl)),
+    Instruction::new(0x3D, "SRL L", |cpu, _| cb_reg(cpu, Reg::L, srl)),
+    Instruction::new(0x3E, "SRL [HL]", |cpu, bus| cb_addr_hl(cpu, bus, srl)),
+    Instruction::new(0x3F, "SRL A", |cpu, _| cb_reg(cpu, Reg::A, srl)),
+    Instruction::new(0x40, "BIT 0, B", |cpu, _| bit(cpu, 0, Reg::B)),
+    Instruction::new(0x41, "BIT 0, C", |cpu, _| bit(cpu, 0, Reg::C)),
+    Instruction::new(0x42, "BIT 0, D", |cpu, _| bit(cpu, 0, Reg::D)),
+    Instruction::new(0x43, "BIT 0, E", |cpu, _| bit(cpu, 0, Reg::E)),
+    Instruction::new(0x44, "BIT 0, H", |cpu, _| bit(cpu, 0, Reg::H)),
+    Instruction::new(0x45, "BIT 0, L", |cpu, _| bit(cpu, 0, Reg::L)),
+    Instruction::new(0x46, "BIT 0, [HL]", |cpu, bus| bit_addr_hl(cpu, bus, 0)),
+    Instruction::new(0x47, "BIT 0, A", |cpu, _| bit(cpu, 0, Reg::A)),
+    Instruction::new(0x48, "BIT 1, B", |cpu, _| bit(cpu, 1, Reg::B)),
+    Instruction::new(0x49, "BIT 1, C", |cpu, _| bit(cpu, 1, Reg::C)),
+    Instruction::new(0x4A, "BIT 1, D", |cpu, _| bit(cpu, 1, Reg::D)),
+    Instruction::new(0x4B, "BIT 1, E", |cpu, _| bit(cpu, 1, Reg::E)),
+    Instruction::new(0x4C, "BIT 1, H", |cpu, _| bit(cpu, 1, Reg::H)),
+    Instruction::new(0x4D, "BIT 1, L", |cpu, _| bit(cpu, 1, Reg::L)),
+    Instruction::new(0x4E, "BIT 1, [HL]", |cpu, bus| bit_addr_hl(cpu, bus, 1)),
+    Instruction::new(0x4F, "BIT 1, A", |cpu, _| bit(cpu, 1, Reg::A)),
+    Instruction::new(0x50, "BIT 2, B", |cpu, _| bit(cpu, 2, Reg::B)),
+    Instruction::new(0x51, "BIT 2, C", |cpu, _| bit(cpu, 2, Reg::C)),
+    Instruction::new(0x52, "BIT 2, D", |cpu, _| bit(cpu, 2, Reg::D)),
+    Instruction::new(0x53, "BIT 2, E", |cpu, _| bit(cpu, 2, Reg::E)),
+    Instruction::new(0x54, "BIT 2, H", |cpu, _| bit(cpu, 2, Reg::H)),
+    Instruction::new(0x55, "BIT 2, L", |cpu, _| bit(cpu, 2, Reg::L)),
+    Instruction::new(0x56, "BIT 2, [HL]", |cpu, bus| bit_addr_hl(cpu, bus, 2)),
+    Instruction::new(0x57, "BIT 2, A", |cpu, _| bit(cpu, 2, Reg::A)),
+    Instruction::new(0x58, "BIT 3, B", |cpu, _| bit(cpu, 3, Reg::B)),
+    Instruction::new(0x59, "BIT 3, C", |cpu, _| bit(cpu, 3, Reg::C)),
+    Instruction::new(0x5A, "BIT 3, D", |cpu, _| bit(cpu, 3, Reg::D)),
+    Instruction::new(0x5B, "BIT 3, E", |cpu, _| bit(cpu, 3, Reg::E)),
+    Instruction::new(0x5C, "BIT 3, H", |cpu, _| bit(cpu, 3, Reg::H)),
+    Instruction::new(0x5D, "BIT 3, L", |cpu, _| bit(cpu, 3, Reg::L)),
+    Instruction::new(0x5E, "BIT 3, [HL]", |cpu, bus| bit_addr_hl(cpu, bus, 3)),
+    Instruction::new(0x5F, "BIT 3, A", |cpu, _| bit(cpu, 3, Reg::A)),
+    Instruction::new(0x60, "BIT 4, B", |cpu, _| bit(cpu, 4, Reg::B)),
+    Instruction::new(0x61, "BIT 4, C", |cpu, _| bit(cpu, 4, Reg::C)),
+    Instruction::new(0x62, "BIT 4, D", |cpu, _| bit(cpu, 4, Reg::D)),
+    Instruction::new(0x63, "BIT 4, E", |cpu, _| bit(cpu, 4, Reg::E)),
+    Instruction::new(0x64, "BIT 4, H", |cpu, _| bit(cpu, 4, Reg::H)),
+    Instruction::new(0x65, "BIT 4, L", |cpu, _| bit(cpu, 4, Reg::L)),
+    Instruction::new(0x66, "BIT 4, [HL]", |cpu, bus| bit_addr_hl(cpu, bus, 4)),
+    Instruction::new(0x67, "BIT 4, A", |cpu, _| bit(cpu, 4, Reg::A)),
+    Instruction::new(0x68, "BIT 5, B", |cpu, _| bit(cpu, 5, Reg::B)),
+    Instruction::new(0x69, "BIT 5, C", |cpu, _| bit(cpu, 5, Reg::C)),
+    Instruction::new(0x6A, "BIT 5, D", |cpu, _| bit(cpu, 5, Reg::D)),
+    Instruction::new(0x6B, "BIT 5, E", |cpu, _| bit(cpu, 5, Reg::E)),
+    Instruction::new(0x6C, "BIT 5, H", |cpu, _| bit(cpu, 5, Reg::H)),
+    Instruction::new(0x6D, "BIT 5, L", |cpu, _| bit(cpu, 5, Reg::L)),
+    Instruction::new(0x6E, "BIT 5, [HL]", |cpu, bus| bit_addr_hl(cpu, bus, 5)),
+    Instruction::new(0x6F, "BIT 5, A", |cpu, _| bit(cpu, 5, Reg::A)),
+    Instruction::new(0x70, "BIT 6, B", |cpu, _| bit(cpu, 6, Reg::B)),
+    Instruction::new(0x71, "BIT 6, C", |cpu, _| bit(cpu, 6, Reg::C)),
+    Instruction::new(0x72, "BIT 6, D", |cpu, _| bit(cpu, 6, Reg::D)),
+    Instruction::new(0x73, "BIT 6, E", |cpu, _| bit(cpu, 6, Reg::E)),
+    Instruction::new(0x74, "BIT 6, H", |cpu, _| bit(cpu, 6, Reg::H)),
+    Instruction::new(0x75, "BIT 6, L", |cpu, _| bit(cpu, 6, Reg::L)),
+    Instruction::new(0x76, "BIT 6, [HL]", |cpu, bus| bit_addr_hl(cpu, bus, 6)),
+    Instruction::new(0x77, "BIT 6, A", |cpu, _| bit(cpu, 6, Reg::A)),
+    Instruction::new(0x78, "BIT 7, B", |cpu, _| bit(cpu, 7, Reg::B)),
+    Instruction::new(0x79, "BIT 7, C", |cpu, _| bit(cpu, 7, Reg::C)),
+    Instruction::new(0x7A, "BIT 7, D", |cpu, _| bit(cpu, 7, Reg::D)),
+    Instruction::new(0x7B, "BIT 7, E", |cpu, _| bit(cpu, 7, Reg::E)),
+    Instruction::new(0x7C, "BIT 7, H", |cpu, _| bit(cpu, 7, Reg::H)),
+    Instruction::new(0x7D, "BIT 7, L", |cpu, _| bit(cpu, 7, Reg::L)),
+    Instruction::new(0x7E, "BIT 7, [HL]", |cpu, bus| bit_addr_hl(cpu, bus, 7)),
+    Instruction::new(0x7F, "BIT 7, A", |cpu, _| bit(cpu, 7, Reg::A)),
+    Instruction::new(0x80, "RES 0, B", |cpu, _| res(cpu, 0, Reg::B)),
+    Instruction::new(0x81, "RES 0, C", |cpu, _| res(cpu, 0, Reg::C)),
+    Instruction::new(0x82, "RES 0, D", |cpu, _| res(cpu, 0, Reg::D)),
+    Instruction::new(0x83, "RES 0, E", |cpu, _| res(cpu, 0, Reg::E)),
+    Instruction::new(0x84, "RES 0, H", |cpu, _| res(cpu, 0, Reg::H)),
+    Instruction::new(0x85, "RES 0, L", |cpu, _| res(cpu, 0, Reg::L)),
+    Instruction::new(0x86, "RES 0, [HL]", |cpu, bus| res_addr_hl(cpu, bus, 0)),
+    Instruction::new(0x87, "RES 0, A", |cpu, _| res(cpu, 0, Reg::A)),
+    Instruction::new(0x88, "RES 1, B", |cpu, _| res(cpu, 1, Reg::B)),
+    Instruction::new(0x89, "RES 1, C", |cpu, _| res(cpu, 1, Reg::C)),
+    Instruction::new(0x8A, "RES 1, D", |cpu, _| res(cpu, 1, Reg::D)),
+    Instruction::new(0x8B, "RES 1, E", |cpu, _| res(cpu, 1, Reg::E)),
+    Instruction::new(0x8C, "RES 1, H", |cpu, _| res(cpu, 1, Reg::H)),
+    Instruction::new(0x8D, "RES 1, L", |cpu, _| res(cpu, 1, Reg::L)),
+    Instruction::new(0x8E, "RES 1, [HL]", |cpu, bus| res_addr_hl(cpu, bus, 1)),
+    Instruction::new(0x8F, "RES 1, A", |cpu, _| res(cpu, 1, Reg::A)),
+    Instruction::new(0x90, "RES 2, B", |cpu, _| res(cpu, 2, Reg::B)),
+    Instruction::new(0x91, "RES 2, C", |cpu, _| res(cpu, 2, Reg::C)),
+    Instruction::new(0x92, "RES 2, D", |cpu, _| res(cpu, 2, Reg::D)),
+    Instruction::new(0x93, "RES 2, E", |cpu, _| res(cpu, 2, Reg::E)),
+    Instruction::new(0x94, "RES 2, H", |cpu, _| res(cpu, 2, Reg::H)),
+    Instruction::new(0x95, "RES 2, L", |cpu, _| res(cpu, 2, Reg::L)),
+    Instruction::new(0x96, "RES 2, [HL]", |cpu, bus| res_addr_hl(cpu, bus, 2)),
+    Instruction::new(0x97, "RES 2, A", |cpu, _| res(cpu, 2, Reg::A)),
+    Instruction::new(0x98, "RES 3, B", |cpu, _| res(cpu, 3, Reg::B)),
+    Instruction::new(0x99, "RES 3, C", |cpu, _| res(cpu, 3, Reg::C)),
+    Instruction::new(0x9A, "RES 3, D", |cpu, _| res(cpu, 3, Reg::D)),
+    Instruction::new(0x9B, "RES 3, E", |cpu, _| res(cpu, 3, Reg::E)),
+    Instruction::new(0x9C, "RES 3, H", |cpu, _| res(cpu, 3, Reg::H)),
+    Instruction::new(0x9D, "RES 3, L", |cpu, _| res(cpu, 3, Reg::L)),
+    Instruction::new(0x9E, "RES 3, [HL]", |cpu, bus| res_addr_hl(cpu, bus, 3)),
+    Instruction::new(0x9F, "RES 3, A", |cpu, _| res(cpu, 3, Reg::A)),
+    Instruction::new(0xA0, "RES 4, B", |cpu, _| res(cpu, 4, Reg::B)),
+    Instruction::new(0xA1, "RES 4, C", |cpu, _| res(cpu, 4, Reg::C)),
+    Instruction::new(0xA2, "RES 4, D", |cpu, _| res(cpu, 4, Reg::D)),
+    Instruction::new(0xA3, "RES 4, E", |cpu, _| res(cpu, 4, Reg::E)),
+    Instruction::new(0xA4, "RES 4, H", |cpu, _| res(cpu, 4, Reg::H)),
+    Instruction::new(0xA5, "RES 4, L", |cpu, _| res(cpu, 4, Reg::L)),
+    Instruction::new(0xA6, "RES 4, [HL]", |cpu, bus| res_addr_hl(cpu, bus, 4)),
+    Instruction::new(0xA7, "RES 4, A", |cpu, _| res(cpu, 4, Reg::A)),
+    Instruction::new(0xA8, "RES 5, B", |cpu, _| res(cpu, 5, Reg::B)),
+    Instruction::new(0xA9, "RES 5, C", |cpu, _| res(cpu, 5, Reg::C)),
+    Instruction::new(0xAA, "RES 5, D", |cpu, _| res(cpu, 5, Reg::D)),
+    Instruction::new(0xAB, "RES 5, E", |cpu, _| res(cpu, 5, Reg::E)),
+    Instruction::new(0xAC, "RES 5, H", |cpu, _| res(cpu, 5, Reg::H)),
+    Instruction::new(0xAD, "RES 5, L", |cpu, _| res(cpu, 5, Reg::L)),
+    Instruction::new(0xAE, "RES 5, [HL]", |cpu, bus| res_addr_hl(cpu, bus, 5)),
+    Instruction::new(0xAF, "RES 5, A", |cpu, _| res(cpu, 5, Reg::A)),
+    Instruction::new(0xB0, "RES 6, B", |cpu, _| res(cpu, 6, Reg::B)),
+    Instruction::new(0xB1, "RES 6, C", |cpu, _| res(cpu, 6, Reg::C)),
+    Instruction::new(0xB2, "RES 6, D", |cpu, _| res(cpu, 6, Reg::D)),
+    Instruction::new(0xB3, "RES 6, E", |cpu, _| res(cpu, 6, Reg::E)),
+    Instruction::new(0xB4, "RES 6, H", |cpu, _| res(cpu, 6, Reg::H)),
+    Instruction::new(0xB5, "RES 6, L", |cpu, _| res(cpu, 6, Reg::L)),
+    Instruction::new(0xB6, "RES 6, [HL]", |cpu, bus| res_addr_hl(cpu, bus, 6)),
+    Instruction::new(0xB7, "RES 6, A", |cpu, _| res(cpu, 6, Reg::A)),
+    Instruction::new(0xB8, "RES 7, B", |cpu, _| res(cpu, 7, Reg::B)),
+    Instruction::new(0xB9, "RES 7, C", |cpu, _| res(cpu, 7, Reg::C)),
+    Instruction::new(0xBA, "RES 7, D", |cpu, _| res(cpu, 7, Reg::D)),
+    Instruction::new(0xBB, "RES 7, E", |cpu, _| res(cpu, 7, Reg::E)),
+    Instruction::new(0xBC, "RES 7, H", |cpu, _| res(cpu, 7, Reg::H)),
+    Instruction::new(0xBD, "RES 7, L", |cpu, _| res(cpu, 7, Reg::L)),
+    Instruction::new(0xBE, "RES 7, [HL]", |cpu, bus| res_addr_hl(cpu, bus, 7)),
+    Instruction::new(0xBF, "RES 7, A", |cpu, _| res(cpu, 7, Reg::A)),
+    Instruction::new(0xC0, "SET 0, B", |cpu, _| set(cpu, 0, Reg::B)),
+    Instruction::new(0xC1, "SET 0, C", |cpu, _| set(cpu, 0, Reg::C)),
+    Instruction::new(0xC2, "SET 0, D", |cpu, _| set(cpu, 0, Reg::D)),
+    Instruction::new(0xC3, "SET 0, E", |cpu, _| set(cpu, 0, Reg::E)),
+    Instruction::new(0xC4, "SET 0, H", |cpu, _| set(cpu, 0, Reg::H)),
+    Instruction::new(0xC5, "SET 0, L", |cpu, _| set(cpu, 0, Reg::L)),
+    Instruction::new(0xC6, "SET 0, [HL]", |cpu, bus| set_addr_hl(cpu, bus, 0)),
+    Instruction::new(0xC7, "SET 0, A", |cpu, _| set(cpu, 0, Reg::A)),
+    Instruction::new(0xC8, "SET 1, B", |cpu, _| set(cpu, 1, Reg::B)),
+    Instruction::new(0xC9, "SET 1, C", |cpu, _| set(cpu, 1, Reg::C)),
+    Instruction::new(0xCA, "SET 1, D", |cpu, _| set(cpu, 1, Reg::D)),
+    Instruction::new(0xCB, "SET 1, E", |cpu, _| set(cpu, 1, Reg::E)),
+    Instruction::new(0xCC, "SET 1, H", |cpu, _| set(cpu, 1, Reg::H)),
+    Instruction::new(0xCD, "SET 1, L", |cpu, _| set(cpu, 1, Reg::L)),
+    Instruction::new(0xCE, "SET 1, [HL]", |cpu, bus| set_addr_hl(cpu, bus, 1)),
+    Instruction::new(0xCF, "SET 1, A", |cpu, _| set(cpu, 1, Reg::A)),
+    Instruction::new(0xD0, "SET 2, B", |cpu, _| set(cpu, 2, Reg::B)),
+    Instruction::new(0xD1, "SET 2, C", |cpu, _| set(cpu, 2, Reg::C)),
+    Instruction::new(0xD2, "SET 2, D", |cpu, _| set(cpu, 2, Reg::D)),
+    Instruction::new(0xD3, "SET 2, E", |cpu, _| set(cpu, 2, Reg::E)),
+    Instruction::new(0xD4, "SET 2, H", |cpu, _| set(cpu, 2, Reg::H)),
+    Instruction::new(0xD5, "SET 2, L", |cpu, _| set(cpu, 2, Reg::L)),
+    Instruction::new(0xD6, "SET 2, [HL]", |cpu, bus| set_addr_hl(cpu, bus, 2)),
+    Instruction::new(0xD7, "SET 2, A", |cpu, _| set(cpu, 2, Reg::A)),
+    Instruction::new(0xD8, "SET 3, B", |cpu, _| set(cpu, 3, Reg::B)),
+    Instruction::new(0xD9, "SET 3, C", |cpu, _| set(cpu, 3, Reg::C)),
+    Instruction::new(0xDA, "SET 3, D", |cpu, _| set(cpu, 3, Reg::D)),
+    Instruction::new(0xDB, "SET 3, E", |cpu, _| set(cpu, 3, Reg::E)),
+    Instruction::new(0xDC, "SET 3, H", |cpu, _| set(cpu, 3, Reg::H)),
+    Instruction::new(0xDD, "SET 3, L", |cpu, _| set(cpu, 3, Reg::L)),
+    Instruction::new(0xDE, "SET 3, [HL]", |cpu, bus| set_addr_hl(cpu, bus, 3)),
+    Instruction::new(0xDF, "SET 3, A", |cpu, _| set(cpu, 3, Reg::A)),
+    Instruction::new(0xE0, "SET 4, B", |cpu, _| set(cpu, 4, Reg::B)),
+    Instruction::new(0xE1, "SET 4, C", |cpu, _| set(cpu, 4, Reg::C)),
+    Instruction::new(0xE2, "SET 4, D", |cpu, _| set(cpu, 4, Reg::D)),
+    Instruction::new(0xE3, "SET 4, E", |cpu, _| set(cpu, 4, Reg::E)),
+    Instruction::new(0xE4, "SET 4, H", |cpu, _| set(cpu, 4, Reg::H)),
+    Instruction::new(0xE5, "SET 4, L", |cpu, _| set(cpu, 4, Reg::L)),
+    Instruction::new(0xE6, "SET 4, [HL]", |cpu, bus| set_addr_hl(cpu, bus, 4)),
+    Instruction::new(0xE7, "SET 4, A", |cpu, _| set(cpu, 4, Reg::A)),
+    Instruction::new(0xE8, "SET 5, B", |cpu, _| set(cpu, 5, Reg::B)),
+    Instruction::new(0xE9, "SET 5, C", |cpu, _| set(cpu, 5, Reg::C)),
+    Instruction::new(0xEA, "SET 5, D", |cpu, _| set(cpu, 5, Reg::D)),
+    Instruction::new(0xEB, "SET 5, E", |cpu, _| set(cpu, 5, Reg::E)),
+    Instruction::new(0xEC, "SET 5, H", |cpu, _| set(cpu, 5, Reg::H)),
+    Instruction::new(0xED, "SET 5, L", |cpu, _| set(cpu, 5, Reg::L)),
+    Instruction::new(0xEE, "SET 5, [HL]", |cpu, bus| set_addr_hl(cpu, bus, 5)),
+    Instruction::new(0xEF, "SET 5, A", |cpu, _| set(cpu, 5, Reg::A)),
+    Instruction::new(0xF0, "SET 6, B", |cpu, _| set(cpu, 6, Reg::B)),
+    Instruction::new(0xF1, "SET 6, C", |cpu, _| set(cpu, 6, Reg::C)),
+    Instruction::new(0xF2, "SET 6, D", |cpu, _| set(cpu, 6, Reg::D)),
+    Instruction::new(0xF3, "SET 6, E", |cpu, _| set(cpu, 6, Reg::E)),
+    Instruction::new(0xF4, "SET 6, H", |cpu, _| set(cpu, 6, Reg::H)),
+    Instruction::new(0xF5, "SET 6, L", |cpu, _| set(cpu, 6, Reg::L)),
+    Instruction::new(0xF6, "SET 6, [HL]", |cpu, bus| set_addr_hl(cpu, bus, 6)),
+    Instruction::new(0xF7, "SET 6, A", |cpu, _| set(cpu, 6, Reg::A)),
+    Instruction::new(0xF8, "SET 7, B", |cpu, _| set(cpu, 7, Reg::B)),
+    Instruction::new(0xF9, "SET 7, C", |cpu, _| set(cpu, 7, Reg::C)),
+    Instruction::new(0xFA, "SET 7, D", |cpu, _| set(cpu, 7, Reg::D)),
+    Instruction::new(0xFB, "SET 7, E", |cpu, _| set(cpu, 7, Reg::E)),
+    Instruction::new(0xFC, "SET 7, H", |cpu, _| set(cpu, 7, Reg::H)),
+    Instruction::new(0xFD, "SET 7, L", |cpu, _| set(cpu, 7, Reg::L)),
+    Instruction::new(0xFE, "SET 7, [HL]", |cpu, bus| set_addr_hl(cpu, bus, 7)),
+    Instruction::new(0xFF, "SET 7, A", |cpu, _| set(cpu, 7, Reg::A)),
+];
@@ -1,8 +1,8 @@
 use crate::{
-    cpu::Cpu,
-    mmu::Mmu,
-    registers::{Flag, Reg, Reg16, RegFlags},
-    utils::has_half_carry,
+    bus::Bus,
+    cpu::{Cpu, CpuState},
+    registers::{Flag, Reg, Reg16},
+    timer::DIV_ADDR,
 };
 
 pub fn ld(cpu: &mut Cpu, from: Reg, to: Reg) -> u8 {
@@ -12,106 +12,1688 @@ pub fn ld(cpu: &mut Cpu, from: Reg, to: Reg) -> u8 {
     4
 }
 
-pub fn ld_imm8(cpu: &mut Cpu, mmu: &mut Mmu, to: Reg) -> u8 {
-    let value = cpu.fetch_immediate_byte(mmu);
+pub fn ld_imm8(cpu: &mut Cpu, bus: &mut dyn Bus, to: Reg) -> u8 {
+    let value = cpu.fetch_immediate_byte(bus);
     cpu.registers.write_reg(to, value);
 
     8
 }
 
-pub fn ld_reg_hl(cpu: &mut Cpu, mmu: &mut Mmu, from: Reg) -> u8 {
+pub fn ld_reg_hl(cpu: &mut Cpu, bus: &mut dyn Bus, from: Reg) -> u8 {
     let addr = cpu.registers.read_reg16(Reg16::HL);
     let value = cpu.registers.read_reg(from);
-    mmu.write_byte(addr, value);
+    bus.write_byte(addr, value);
 
     8
 }
 
-pub fn ld_reg_addr(cpu: &mut Cpu, mmu: &mut Mmu, from: Reg, to: Reg16) -> u8 {
+pub fn ld_reg_addr(cpu: &mut Cpu, bus: &mut dyn Bus, from: Reg, to: Reg16) -> u8 {
     let addr = cpu.registers.read_reg16(to);
     let value = cpu.registers.read_reg(from);
-    mmu.write_byte(addr, value);
+    bus.write_byte(addr, value);
 
     8
 }
 
-pub fn ld_imm16(cpu: &mut Cpu, mmu: &mut Mmu, reg: Reg16) -> u8 {
-    let value = mmu.read_word(cpu.registers.pc);
+pub fn ld_imm16(cpu: &mut Cpu, bus: &mut dyn Bus, reg: Reg16) -> u8 {
+    let value = cpu.fetch_immediate_word(bus);
     cpu.registers.write_reg16(reg, value);
 
     12
 }
 
-pub fn ld_imm8_hl(cpu: &mut Cpu, mmu: &mut Mmu) -> u8 {
-    let value = cpu.fetch_immediate_byte(mmu);
+pub fn ld_imm8_hl(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = cpu.fetch_immediate_byte(bus);
     let addr = cpu.registers.read_reg16(Reg16::HL);
-    mmu.write_byte(addr, value);
+    bus.write_byte(addr, value);
 
     12
 }
 
-pub fn ld_reg_imm_addr(cpu: &mut Cpu, mmu: &mut Mmu, from: Reg) -> u8 {
-    let addr = cpu.fetch_immediate_word(mmu);
+pub fn ld_reg_imm_addr(cpu: &mut Cpu, bus: &mut dyn Bus, from: Reg) -> u8 {
+    let addr = cpu.fetch_immediate_word(bus);
     let value = cpu.registers.read_reg(from);
-    mmu.write_byte(addr, value);
+    bus.write_byte(addr, value);
 
     16
 }
 
-pub fn ld_addr_reg(cpu: &mut Cpu, mmu: &mut Mmu, reg_addr: Reg16, to: Reg) -> u8 {
+pub fn ld_addr_reg(cpu: &mut Cpu, bus: &mut dyn Bus, reg_addr: Reg16, to: Reg) -> u8 {
     let addr = cpu.registers.read_reg16(reg_addr);
-    let value = mmu.read_byte(addr);
+    let value = bus.read_byte(addr);
     cpu.registers.write_reg(to, value);
     8
 }
 
+pub fn ld_sp_hl(cpu: &mut Cpu) -> u8 {
+    cpu.registers.sp = cpu.registers.read_reg16(Reg16::HL);
+
+    8
+}
+
+pub fn ld_hl_sp_imm8(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = add_sp_offset(cpu, bus);
+    cpu.registers.write_reg16(Reg16::HL, value);
+
+    12
+}
+
+pub fn push(cpu: &mut Cpu, bus: &mut dyn Bus, reg: Reg16) -> u8 {
+    let value = cpu.registers.read_reg16(reg);
+    // SP is decremented during an internal cycle before the writes
+    bus.tick();
+    cpu.push_word(bus, value);
+
+    16
+}
+
+pub fn pop(cpu: &mut Cpu, bus: &mut dyn Bus, reg: Reg16) -> u8 {
+    let value = cpu.pop_word(bus);
+    cpu.registers.write_reg16(reg, value);
+
+    12
+}
+
+pub fn inc16(cpu: &mut Cpu, reg: Reg16) -> u8 {
+    let value = cpu.registers.read_reg16(reg.clone());
+    cpu.registers.write_reg16(reg, value.wrapping_add(1));
+
+    8
+}
+
+pub fn dec16(cpu: &mut Cpu, reg: Reg16) -> u8 {
+    let value = cpu.registers.read_reg16(reg.clone());
+    cpu.registers.write_reg16(reg, value.wrapping_sub(1));
+
+    8
+}
+
+pub fn add_hl_r16(cpu: &mut Cpu, from: Reg16) -> u8 {
+    let hl = cpu.registers.read_reg16(Reg16::HL);
+    let value = cpu.registers.read_reg16(from);
+    let (result, did_overflow) = hl.overflowing_add(value);
+
+    cpu.registers.write_reg16(Reg16::HL, result);
+
+    // Set flags, Z is left untouched
+    cpu.registers.set_flag(Flag::N, false);
+    cpu.registers
+        .set_flag(Flag::H, (hl & 0x0FFF) + (value & 0x0FFF) > 0x0FFF);
+    cpu.registers.set_flag(Flag::C, did_overflow);
+
+    8
+}
+
+pub fn add_sp_imm8(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    cpu.registers.sp = add_sp_offset(cpu, bus);
+
+    16
+}
+
 pub fn add(cpu: &mut Cpu, from: Reg) -> u8 {
+    let value = cpu.registers.read_reg(from);
+    alu_add(cpu, value, false);
+
+    4
+}
+
+pub fn add_addr_hl(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = read_addr_hl(cpu, bus);
+    alu_add(cpu, value, false);
+
+    8
+}
+
+pub fn add_imm8(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = cpu.fetch_immediate_byte(bus);
+    alu_add(cpu, value, false);
+
+    8
+}
+
+pub fn adc(cpu: &mut Cpu, from: Reg) -> u8 {
+    let value = cpu.registers.read_reg(from);
+    alu_add(cpu, value, cpu.registers.f.carry);
+
+    4
+}
+
+pub fn adc_addr_hl(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = read_addr_hl(cpu, bus);
+    alu_add(cpu, value, cpu.registers.f.carry);
+
+    8
+}
+
+pub fn adc_imm8(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = cpu.fetch_immediate_byte(bus);
+    alu_add(cpu, value, cpu.registers.f.carry);
+
+    8
+}
+
+pub fn sub(cpu: &mut Cpu, from: Reg) -> u8 {
+    let value = cpu.registers.read_reg(from);
+    alu_sub(cpu, value, false, true);
+
+    4
+}
+
+pub fn sub_addr_hl(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = read_addr_hl(cpu, bus);
+    alu_sub(cpu, value, false, true);
+
+    8
+}
+
+pub fn sub_imm8(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = cpu.fetch_immediate_byte(bus);
+    alu_sub(cpu, value, false, true);
+
+    8
+}
+
+pub fn sbc(cpu: &mut Cpu, from: Reg) -> u8 {
+    let value = cpu.registers.read_reg(from);
+    alu_sub(cpu, value, cpu.registers.f.carry, true);
+
+    4
+}
+
+pub fn sbc_addr_hl(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = read_addr_hl(cpu, bus);
+    alu_sub(cpu, value, cpu.registers.f.carry, true);
+
+    8
+}
+
+pub fn sbc_imm8(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = cpu.fetch_immediate_byte(bus);
+    alu_sub(cpu, value, cpu.registers.f.carry, true);
+
+    8
+}
+
+pub fn and(cpu: &mut Cpu, from: Reg) -> u8 {
+    let value = cpu.registers.read_reg(from);
+    alu_and(cpu, value);
+
+    4
+}
+
+pub fn and_addr_hl(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = read_addr_hl(cpu, bus);
+    alu_and(cpu, value);
+
+    8
+}
+
+pub fn and_imm8(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = cpu.fetch_immediate_byte(bus);
+    alu_and(cpu, value);
+
+    8
+}
+
+pub fn xor(cpu: &mut Cpu, from: Reg) -> u8 {
+    let value = cpu.registers.read_reg(from);
+    alu_xor(cpu, value);
+
+    4
+}
+
+pub fn xor_addr_hl(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = read_addr_hl(cpu, bus);
+    alu_xor(cpu, value);
+
+    8
+}
+
+pub fn xor_imm8(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = cpu.fetch_immediate_byte(bus);
+    alu_xor(cpu, value);
+
+    8
+}
+
+pub fn or(cpu: &mut Cpu, from: Reg) -> u8 {
+    let value = cpu.registers.read_reg(from);
+    alu_or(cpu, value);
+
+    4
+}
+
+pub fn or_addr_hl(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = read_addr_hl(cpu, bus);
+    alu_or(cpu, value);
+
+    8
+}
+
+pub fn or_imm8(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = cpu.fetch_immediate_byte(bus);
+    alu_or(cpu, value);
+
+    8
+}
+
+pub fn cp(cpu: &mut Cpu, from: Reg) -> u8 {
+    let value = cpu.registers.read_reg(from);
+    alu_sub(cpu, value, false, false);
+
+    4
+}
+
+pub fn cp_addr_hl(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = read_addr_hl(cpu, bus);
+    alu_sub(cpu, value, false, false);
+
+    8
+}
+
+pub fn cp_imm8(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let value = cpu.fetch_immediate_byte(bus);
+    alu_sub(cpu, value, false, false);
+
+    8
+}
+
+pub enum Condition {
+    NZ,
+    Z,
+    NC,
+    C,
+}
+
+impl Condition {
+    fn is_met(&self, cpu: &Cpu) -> bool {
+        match self {
+            Condition::NZ => !cpu.registers.get_flag(Flag::Z),
+            Condition::Z => cpu.registers.get_flag(Flag::Z),
+            Condition::NC => !cpu.registers.get_flag(Flag::C),
+            Condition::C => cpu.registers.get_flag(Flag::C),
+        }
+    }
+}
+
+pub fn jr(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let offset = cpu.fetch_immediate_byte(bus) as i8;
+    cpu.registers.pc = cpu.registers.pc.wrapping_add_signed(offset as i16);
+
+    12
+}
+
+pub fn jr_cc(cpu: &mut Cpu, bus: &mut dyn Bus, cond: Condition) -> u8 {
+    let offset = cpu.fetch_immediate_byte(bus) as i8;
+
+    if !cond.is_met(cpu) {
+        return 8;
+    }
+
+    cpu.registers.pc = cpu.registers.pc.wrapping_add_signed(offset as i16);
+
+    12
+}
+
+pub fn jp(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    cpu.registers.pc = cpu.fetch_immediate_word(bus);
+
+    16
+}
+
+pub fn jp_cc(cpu: &mut Cpu, bus: &mut dyn Bus, cond: Condition) -> u8 {
+    let addr = cpu.fetch_immediate_word(bus);
+
+    if !cond.is_met(cpu) {
+        return 12;
+    }
+
+    cpu.registers.pc = addr;
+
+    16
+}
+
+pub fn jp_hl(cpu: &mut Cpu) -> u8 {
+    cpu.registers.pc = cpu.registers.read_reg16(Reg16::HL);
+
+    4
+}
+
+pub fn call(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let addr = cpu.fetch_immediate_word(bus);
+    bus.tick();
+    cpu.push_word(bus, cpu.registers.pc);
+    cpu.registers.pc = addr;
+
+    24
+}
+
+pub fn call_cc(cpu: &mut Cpu, bus: &mut dyn Bus, cond: Condition) -> u8 {
+    let addr = cpu.fetch_immediate_word(bus);
+
+    if !cond.is_met(cpu) {
+        return 12;
+    }
+
+    bus.tick();
+    cpu.push_word(bus, cpu.registers.pc);
+    cpu.registers.pc = addr;
+
+    24
+}
+
+pub fn ret(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    cpu.registers.pc = cpu.pop_word(bus);
+
+    16
+}
+
+pub fn ret_cc(cpu: &mut Cpu, bus: &mut dyn Bus, cond: Condition) -> u8 {
+    // The condition is checked during an internal cycle before popping
+    bus.tick();
+    if !cond.is_met(cpu) {
+        return 8;
+    }
+
+    cpu.registers.pc = cpu.pop_word(bus);
+
+    20
+}
+
+pub fn reti(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    cpu.registers.pc = cpu.pop_word(bus);
+    cpu.ime = true;
+
+    16
+}
+
+pub fn di(cpu: &mut Cpu) -> u8 {
+    cpu.ime = false;
+    cpu.ime_scheduled = false;
+
+    4
+}
+
+pub fn ei(cpu: &mut Cpu) -> u8 {
+    cpu.ime_scheduled = true;
+
+    4
+}
+
+pub fn halt(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    if !cpu.ime && bus.pending_interrupts() != 0 {
+        cpu.halt_bug = true;
+    } else {
+        cpu.state = CpuState::Halted;
+    }
+
+    4
+}
+
+pub fn stop(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    // STOP is followed by a padding byte that is skipped
+    cpu.fetch_immediate_byte(bus);
+    // These are side effects of STOP itself rather than bus cycles
+    bus.write(DIV_ADDR, 0);
+
+    // On CGB, a STOP with the speed switch armed performs it instead
+    if !bus.switch_speed() {
+        cpu.state = CpuState::Stopped;
+    }
+
+    8
+}
+
+pub fn rst(cpu: &mut Cpu, bus: &mut dyn Bus, vector: u16) -> u8 {
+    bus.tick();
+    cpu.push_word(bus, cpu.registers.pc);
+    cpu.registers.pc = vector;
+
+    16
+}
+
+pub fn inc(cpu: &mut Cpu, reg: Reg) -> u8 {
+    let value = cpu.registers.read_reg(reg.clone());
+    let result = alu_inc(cpu, value);
+    cpu.registers.write_reg(reg, result);
+
+    4
+}
+
+pub fn inc_addr_hl(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let addr = cpu.registers.read_reg16(Reg16::HL);
+    let value = bus.read_byte(addr);
+    let result = alu_inc(cpu, value);
+    bus.write_byte(addr, result);
+
+    12
+}
+
+pub fn dec(cpu: &mut Cpu, reg: Reg) -> u8 {
+    let value = cpu.registers.read_reg(reg.clone());
+    let result = alu_dec(cpu, value);
+    cpu.registers.write_reg(reg, result);
+
+    4
+}
+
+pub fn dec_addr_hl(cpu: &mut Cpu, bus: &mut dyn Bus) -> u8 {
+    let addr = cpu.registers.read_reg16(Reg16::HL);
+    let value = bus.read_byte(addr);
+    let result = alu_dec(cpu, value);
+    bus.write_byte(addr, result);
+
+    12
+}
+
+// Adjusts A back into BCD after an ADD/ADC (N=0) or SUB/SBC (N=1)
+pub fn daa(cpu: &mut Cpu) -> u8 {
+    let mut a = cpu.registers.read_reg(Reg::A);
+    let mut carry = cpu.registers.get_flag(Flag::C);
+    let half_carry = cpu.registers.get_flag(Flag::H);
+
+    if cpu.registers.get_flag(Flag::N) {
+        if carry {
+            a = a.wrapping_sub(0x60);
+        }
+        if half_carry {
+            a = a.wrapping_sub(0x06);
+        }
+    } else {
+        if carry || a > 0x99 {
+            a = a.wrapping_add(0x60);
+            carry = true;
+        }
+        if half_carry || (a & 0x0F) > 0x09 {
+            a = a.wrapping_add(0x06);
+        }
+    }
+
+    cpu.registers.write_reg(Reg::A, a);
+
+    // Set flags, N is left untouched
+    cpu.registers.set_flag(Flag::Z, a == 0);
+    cpu.registers.set_flag(Flag::H, false);
+    cpu.registers.set_flag(Flag::C, carry);
+
+    4
+}
+
+pub fn cpl(cpu: &mut Cpu) -> u8 {
+    let a = cpu.registers.read_reg(Reg::A);
+    cpu.registers.write_reg(Reg::A, !a);
+
+    cpu.registers.set_flag(Flag::N, true);
+    cpu.registers.set_flag(Flag::H, true);
+
+    4
+}
+
+pub fn scf(cpu: &mut Cpu) -> u8 {
+    cpu.registers.set_flag(Flag::N, false);
+    cpu.registers.set_flag(Flag::H, false);
+    cpu.registers.set_flag(Flag::C, true);
+
+    4
+}
+
+pub fn ccf(cpu: &mut Cpu) -> u8 {
+    let carry = cpu.registers.get_flag(Flag::C);
+
+    cpu.registers.set_flag(Flag::N, false);
+    cpu.registers.set_flag(Flag::H, false);
+    cpu.registers.set_flag(Flag::C, !carry);
+
+    4
+}
+
+// RLCA, RRCA, RLA and RRA behave like their CB counterparts on A, except Z is
+// always cleared.
+pub fn rotate_a(cpu: &mut Cpu, op: fn(&mut Cpu, u8) -> u8) -> u8 {
+    let value = cpu.registers.read_reg(Reg::A);
+    let result = op(cpu, value);
+    cpu.registers.write_reg(Reg::A, result);
+    cpu.registers.set_flag(Flag::Z, false);
+
+    4
+}
+
+pub fn cb_reg(cpu: &mut Cpu, reg: Reg, op: fn(&mut Cpu, u8) -> u8) -> u8 {
+    let value = cpu.registers.read_reg(reg.clone());
+    let result = op(cpu, value);
+    cpu.registers.write_reg(reg, result);
+
+    8
+}
+
+pub fn cb_addr_hl(cpu: &mut Cpu, bus: &mut dyn Bus, op: fn(&mut Cpu, u8) -> u8) -> u8 {
+    let addr = cpu.registers.read_reg16(Reg16::HL);
+    let value = bus.read_byte(addr);
+    let result = op(cpu, value);
+    bus.write_byte(addr, result);
+
+    16
+}
+
+pub fn rlc(cpu: &mut Cpu, value: u8) -> u8 {
+    let result = value.rotate_left(1);
+    set_shift_flags(cpu, result, value & 0x80 != 0);
+
+    result
+}
+
+pub fn rrc(cpu: &mut Cpu, value: u8) -> u8 {
+    let result = value.rotate_right(1);
+    set_shift_flags(cpu, result, value & 0x01 != 0);
+
+    result
+}
+
+pub fn rl(cpu: &mut Cpu, value: u8) -> u8 {
+    let result = (value << 1) | cpu.registers.f.carry as u8;
+    set_shift_flags(cpu, result, value & 0x80 != 0);
+
+    result
+}
+
+pub fn rr(cpu: &mut Cpu, value: u8) -> u8 {
+    let result = (value >> 1) | ((cpu.registers.f.carry as u8) << 7);
+    set_shift_flags(cpu, result, value & 0x01 != 0);
+
+    result
+}
+
+pub fn sla(cpu: &mut Cpu, value: u8) -> u8 {
+    let result = value << 1;
+    set_shift_flags(cpu, result, value & 0x80 != 0);
+
+    result
+}
+
+pub fn sra(cpu: &mut Cpu, value: u8) -> u8 {
+    let result = (value >> 1) | (value & 0x80);
+    set_shift_flags(cpu, result, value & 0x01 != 0);
+
+    result
+}
+
+pub fn swap(cpu: &mut Cpu, value: u8) -> u8 {
+    let result = value.rotate_left(4);
+    set_shift_flags(cpu, result, false);
+
+    result
+}
+
+pub fn srl(cpu: &mut Cpu, value: u8) -> u8 {
+    let result = value >> 1;
+    set_shift_flags(cpu, result, value & 0x01 != 0);
+
+    result
+}
+
+pub fn bit(cpu: &mut Cpu, n: u8, reg: Reg) -> u8 {
+    let value = cpu.registers.read_reg(reg);
+    test_bit(cpu, n, value);
+
+    8
+}
+
+pub fn bit_addr_hl(cpu: &mut Cpu, bus: &mut dyn Bus, n: u8) -> u8 {
+    let value = read_addr_hl(cpu, bus);
+    test_bit(cpu, n, value);
+
+    12
+}
+
+pub fn res(cpu: &mut Cpu, n: u8, reg: Reg) -> u8 {
+    let value = cpu.registers.read_reg(reg.clone());
+    cpu.registers.write_reg(reg, value & !(1 << n));
+
+    8
+}
+
+pub fn res_addr_hl(cpu: &mut Cpu, bus: &mut dyn Bus, n: u8) -> u8 {
+    let addr = cpu.registers.read_reg16(Reg16::HL);
+    let value = bus.read_byte(addr);
+    bus.write_byte(addr, value & !(1 << n));
+
+    16
+}
+
+pub fn set(cpu: &mut Cpu, n: u8, reg: Reg) -> u8 {
+    let value = cpu.registers.read_reg(reg.clone());
+    cpu.registers.write_reg(reg, value | (1 << n));
+
+    8
+}
+
+pub fn set_addr_hl(cpu: &mut Cpu, bus: &mut dyn Bus, n: u8) -> u8 {
+    let addr = cpu.registers.read_reg16(Reg16::HL);
+    let value = bus.read_byte(addr);
+    bus.write_byte(addr, value | (1 << n));
+
+    16
+}
+
+fn set_shift_flags(cpu: &mut Cpu, result: u8, carry: bool) {
+    cpu.registers.set_flag(Flag::Z, result == 0);
+    cpu.registers.set_flag(Flag::N, false);
+    cpu.registers.set_flag(Flag::H, false);
+    cpu.registers.set_flag(Flag::C, carry);
+}
+
+// BIT leaves the carry flag untouched.
+fn test_bit(cpu: &mut Cpu, n: u8, value: u8) {
+    cpu.registers.set_flag(Flag::Z, value & (1 << n) == 0);
+    cpu.registers.set_flag(Flag::N, false);
+    cpu.registers.set_flag(Flag::H, true);
+}
+
+// Shared by ADD SP, e8 and LD HL, SP+e8. The offset is signed, but H and C
+// come from an unsigned add of the offset to the low byte of SP.
+fn add_sp_offset(cpu: &mut Cpu, bus: &mut dyn Bus) -> u16 {
+    let sp = cpu.registers.sp;
+    let offset = cpu.fetch_immediate_byte(bus);
+
+    cpu.registers.set_flag(Flag::Z, false);
+    cpu.registers.set_flag(Flag::N, false);
+    cpu.registers
+        .set_flag(Flag::H, (sp & 0x0F) + (offset as u16 & 0x0F) > 0x0F);
+    cpu.registers
+        .set_flag(Flag::C, (sp & 0xFF) + offset as u16 > 0xFF);
+
+    sp.wrapping_add_signed(offset as i8 as i16)
+}
+
+fn read_addr_hl(cpu: &Cpu, bus: &mut dyn Bus) -> u8 {
+    let addr = cpu.registers.read_reg16(Reg16::HL);
+    bus.read_byte(addr)
+}
+
+fn alu_add(cpu: &mut Cpu, value: u8, carry: bool) {
     let a = cpu.registers.read_reg(Reg::A);
-    let value = cpu.registers.read_reg(from);
-    let (result, did_overflow) = a.overflowing_add(value);
+    let carry = carry as u8;
+    let result = a.wrapping_add(value).wrapping_add(carry);
 
     cpu.registers.write_reg(Reg::A, result);
 
     // Set flags
     cpu.registers.set_flag(Flag::Z, result == 0);
     cpu.registers.set_flag(Flag::N, false);
-    cpu.registers.set_flag(Flag::H, has_half_carry(a, value));
-    cpu.registers.set_flag(Flag::C, did_overflow);
+    cpu.registers
+        .set_flag(Flag::H, (a & 0x0F) + (value & 0x0F) + carry > 0x0F);
+    cpu.registers
+        .set_flag(Flag::C, a as u16 + value as u16 + carry as u16 > 0xFF);
+}
 
-    4
+// Shared by SUB, SBC and CP; CP only sets the flags and leaves A untouched.
+fn alu_sub(cpu: &mut Cpu, value: u8, carry: bool, store: bool) {
+    let a = cpu.registers.read_reg(Reg::A);
+    let carry = carry as u8;
+    let result = a.wrapping_sub(value).wrapping_sub(carry);
+
+    if store {
+        cpu.registers.write_reg(Reg::A, result);
+    }
+
+    // Set flags
+    cpu.registers.set_flag(Flag::Z, result == 0);
+    cpu.registers.set_flag(Flag::N, true);
+    cpu.registers
+        .set_flag(Flag::H, (a & 0x0F) < (value & 0x0F) + carry);
+    cpu.registers
+        .set_flag(Flag::C, (a as u16) < value as u16 + carry as u16);
 }
 
-pub fn adc(cpu: &mut Cpu, from: Reg) -> u8 {
-    let a = cpu.registers.read_reg(Reg::A) as u16;
-    let value = cpu.registers.read_reg(from) as u16;
-    let carry = if cpu.registers.f.carry { 1 } else { 0 };
+// INC and DEC leave the carry flag untouched.
+fn alu_inc(cpu: &mut Cpu, value: u8) -> u8 {
+    let result = value.wrapping_add(1);
+
+    cpu.registers.set_flag(Flag::Z, result == 0);
+    cpu.registers.set_flag(Flag::N, false);
+    cpu.registers.set_flag(Flag::H, value & 0x0F == 0x0F);
+
+    result
+}
+
+fn alu_dec(cpu: &mut Cpu, value: u8) -> u8 {
+    let result = value.wrapping_sub(1);
+
+    cpu.registers.set_flag(Flag::Z, result == 0);
+    cpu.registers.set_flag(Flag::N, true);
+    cpu.registers.set_flag(Flag::H, value & 0x0F == 0x00);
+
+    result
+}
 
-    let result: u16 = a + value + carry;
+fn alu_and(cpu: &mut Cpu, value: u8) {
+    let result = cpu.registers.read_reg(Reg::A) & value;
 
-    cpu.registers.write_reg(Reg::A, result as u8);
+    cpu.registers.write_reg(Reg::A, result);
 
     // Set flags
-    cpu.registers.set_flag(Flag::Z, (result & 0xFF) == 0);
+    cpu.registers.set_flag(Flag::Z, result == 0);
     cpu.registers.set_flag(Flag::N, false);
-    cpu.registers
-        .set_flag(Flag::H, ((a & 0x0F) + (value & 0x0F) + carry) > 0x0F);
-    cpu.registers.set_flag(Flag::C, result > 0xFF);
+    cpu.registers.set_flag(Flag::H, true);
+    cpu.registers.set_flag(Flag::C, false);
+}
 
-    4
+fn alu_xor(cpu: &mut Cpu, value: u8) {
+    let result = cpu.registers.read_reg(Reg::A) ^ value;
+
+    cpu.registers.write_reg(Reg::A, result);
+
+    // Set flags
+    cpu.registers.set_flag(Flag::Z, result == 0);
+    cpu.registers.set_flag(Flag::N, false);
+    cpu.registers.set_flag(Flag::H, false);
+    cpu.registers.set_flag(Flag::C, false);
 }
 
-pub fn add_imm8(cpu: &mut Cpu, mmu: &mut Mmu) -> u8 {
-    let a = cpu.registers.read_reg(Reg::A);
-    let value = cpu.fetch_immediate_byte(mmu);
-    let (result, did_overflow) = a.overflowing_add(value);
+fn alu_or(cpu: &mut Cpu, value: u8) {
+    let result = cpu.registers.read_reg(Reg::A) | value;
 
     cpu.registers.write_reg(Reg::A, result);
 
     // Set flags
     cpu.registers.set_flag(Flag::Z, result == 0);
     cpu.registers.set_flag(Flag::N, false);
-    cpu.registers.set_flag(Flag::H, has_half_carry(a, value));
-    cpu.registers.set_flag(Flag::C, did_overflow);
+    cpu.registers.set_flag(Flag::H, false);
+    cpu.registers.set_flag(Flag::C, false);
+}
 
-    8
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mmu::Mmu;
+
+    fn flags(cpu: &Cpu) -> (bool, bool, bool, bool) {
+        let f = &cpu.registers.f;
+        (f.zero, f.subtract, f.half_carry, f.carry)
+    }
+
+    #[test]
+    fn test_add_sets_half_carry_and_carry() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0xF8;
+        cpu.registers.b = 0x08;
+
+        let cycles = add(&mut cpu, Reg::B);
+
+        assert_eq!(cycles, 4);
+        assert_eq!(cpu.registers.a, 0x00);
+        assert_eq!(flags(&cpu), (true, false, true, true));
+    }
+
+    #[test]
+    fn test_adc_adds_carry_in() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x0E;
+        cpu.registers.c = 0x01;
+        cpu.registers.f.carry = true;
+
+        adc(&mut cpu, Reg::C);
+
+        assert_eq!(cpu.registers.a, 0x10);
+        assert_eq!(flags(&cpu), (false, false, true, false));
+    }
+
+    #[test]
+    fn test_adc_a_a_with_carry() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x80;
+        cpu.registers.f.carry = true;
+
+        adc(&mut cpu, Reg::A);
+
+        assert_eq!(cpu.registers.a, 0x01);
+        assert_eq!(flags(&cpu), (false, false, false, true));
+    }
+
+    #[test]
+    fn test_add_addr_hl() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.a = 0x01;
+        cpu.registers.write_hl(0xC000);
+        mmu.poke(0xC000, 0x02);
+
+        let cycles = add_addr_hl(&mut cpu, &mut mmu);
+
+        assert_eq!(cycles, 8);
+        assert_eq!(cpu.registers.a, 0x03);
+    }
+
+    #[test]
+    fn test_sub_to_zero() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x3E;
+        cpu.registers.e = 0x3E;
+
+        let cycles = sub(&mut cpu, Reg::E);
+
+        assert_eq!(cycles, 4);
+        assert_eq!(cpu.registers.a, 0x00);
+        assert_eq!(flags(&cpu), (true, true, false, false));
+    }
+
+    #[test]
+    fn test_sub_half_borrow() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x3E;
+        cpu.registers.e = 0x0F;
+
+        sub(&mut cpu, Reg::E);
+
+        assert_eq!(cpu.registers.a, 0x2F);
+        assert_eq!(flags(&cpu), (false, true, true, false));
+    }
+
+    #[test]
+    fn test_sub_borrow() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x3E;
+        cpu.registers.e = 0x40;
+
+        sub(&mut cpu, Reg::E);
+
+        assert_eq!(cpu.registers.a, 0xFE);
+        assert_eq!(flags(&cpu), (false, true, false, true));
+    }
+
+    #[test]
+    fn test_sub_imm8() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.a = 0x10;
+        cpu.registers.pc = 0xC000;
+        mmu.poke(0xC000, 0x01);
+
+        let cycles = sub_imm8(&mut cpu, &mut mmu);
+
+        assert_eq!(cycles, 8);
+        assert_eq!(cpu.registers.pc, 0xC001);
+        assert_eq!(cpu.registers.a, 0x0F);
+        assert_eq!(flags(&cpu), (false, true, true, false));
+    }
+
+    #[test]
+    fn test_sbc_subtracts_carry_in() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x3B;
+        cpu.registers.h = 0x2A;
+        cpu.registers.f.carry = true;
+
+        sbc(&mut cpu, Reg::H);
+
+        assert_eq!(cpu.registers.a, 0x10);
+        assert_eq!(flags(&cpu), (false, true, false, false));
+    }
+
+    #[test]
+    fn test_sbc_carry_in_causes_borrow() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x00;
+        cpu.registers.b = 0x00;
+        cpu.registers.f.carry = true;
+
+        sbc(&mut cpu, Reg::B);
+
+        assert_eq!(cpu.registers.a, 0xFF);
+        assert_eq!(flags(&cpu), (false, true, true, true));
+    }
+
+    #[test]
+    fn test_sbc_a_a_with_carry() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x42;
+        cpu.registers.f.carry = true;
+
+        sbc(&mut cpu, Reg::A);
+
+        assert_eq!(cpu.registers.a, 0xFF);
+        assert_eq!(flags(&cpu), (false, true, true, true));
+    }
+
+    #[test]
+    fn test_sbc_addr_hl() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.a = 0x3B;
+        cpu.registers.f.carry = true;
+        cpu.registers.write_hl(0xC000);
+        mmu.poke(0xC000, 0x4F);
+
+        let cycles = sbc_addr_hl(&mut cpu, &mut mmu);
+
+        assert_eq!(cycles, 8);
+        assert_eq!(cpu.registers.a, 0xEB);
+        assert_eq!(flags(&cpu), (false, true, true, true));
+    }
+
+    #[test]
+    fn test_and_sets_half_carry() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x5A;
+        cpu.registers.l = 0x3F;
+        cpu.registers.f.carry = true;
+
+        let cycles = and(&mut cpu, Reg::L);
+
+        assert_eq!(cycles, 4);
+        assert_eq!(cpu.registers.a, 0x1A);
+        assert_eq!(flags(&cpu), (false, false, true, false));
+    }
+
+    #[test]
+    fn test_and_imm8_zero() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.a = 0x5A;
+        cpu.registers.pc = 0xC000;
+        mmu.poke(0xC000, 0x00);
+
+        and_imm8(&mut cpu, &mut mmu);
+
+        assert_eq!(cpu.registers.a, 0x00);
+        assert_eq!(flags(&cpu), (true, false, true, false));
+    }
+
+    #[test]
+    fn test_xor_a_a_clears_a() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0xFF;
+        cpu.registers.f.carry = true;
+        cpu.registers.f.half_carry = true;
+        cpu.registers.f.subtract = true;
+
+        xor(&mut cpu, Reg::A);
+
+        assert_eq!(cpu.registers.a, 0x00);
+        assert_eq!(flags(&cpu), (true, false, false, false));
+    }
+
+    #[test]
+    fn test_xor_addr_hl() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.a = 0xFF;
+        cpu.registers.write_hl(0xC000);
+        mmu.poke(0xC000, 0x8A);
+
+        xor_addr_hl(&mut cpu, &mut mmu);
+
+        assert_eq!(cpu.registers.a, 0x75);
+        assert_eq!(flags(&cpu), (false, false, false, false));
+    }
+
+    #[test]
+    fn test_or() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x5A;
+        cpu.registers.d = 0x03;
+        cpu.registers.f.carry = true;
+
+        or(&mut cpu, Reg::D);
+
+        assert_eq!(cpu.registers.a, 0x5B);
+        assert_eq!(flags(&cpu), (false, false, false, false));
+    }
+
+    #[test]
+    fn test_or_zero() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.a = 0x00;
+        cpu.registers.pc = 0xC000;
+        mmu.poke(0xC000, 0x00);
+
+        or_imm8(&mut cpu, &mut mmu);
+
+        assert_eq!(flags(&cpu), (true, false, false, false));
+    }
+
+    #[test]
+    fn test_cp_equal_leaves_a() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x3C;
+        cpu.registers.b = 0x3C;
+
+        cp(&mut cpu, Reg::B);
+
+        assert_eq!(cpu.registers.a, 0x3C);
+        assert_eq!(flags(&cpu), (true, true, false, false));
+    }
+
+    #[test]
+    fn test_cp_imm8_borrow() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.a = 0x3C;
+        cpu.registers.pc = 0xC000;
+        mmu.poke(0xC000, 0x40);
+
+        let cycles = cp_imm8(&mut cpu, &mut mmu);
+
+        assert_eq!(cycles, 8);
+        assert_eq!(cpu.registers.a, 0x3C);
+        assert_eq!(flags(&cpu), (false, true, false, true));
+    }
+
+    #[test]
+    fn test_cp_addr_hl_half_borrow() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.a = 0x3C;
+        cpu.registers.write_hl(0xC000);
+        mmu.poke(0xC000, 0x2F);
+
+        cp_addr_hl(&mut cpu, &mut mmu);
+
+        assert_eq!(cpu.registers.a, 0x3C);
+        assert_eq!(flags(&cpu), (false, true, true, false));
+    }
+
+    #[test]
+    fn test_jr_backwards() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC001;
+        mmu.poke(0xC001, 0xFE);
+
+        let cycles = jr(&mut cpu, &mut mmu);
+
+        assert_eq!(cycles, 12);
+        assert_eq!(cpu.registers.pc, 0xC000);
+    }
+
+    #[test]
+    fn test_jr_cc_not_taken() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC001;
+        cpu.registers.f.zero = true;
+        mmu.poke(0xC001, 0x10);
+
+        let cycles = jr_cc(&mut cpu, &mut mmu, Condition::NZ);
+
+        assert_eq!(cycles, 8);
+        assert_eq!(cpu.registers.pc, 0xC002);
+    }
+
+    #[test]
+    fn test_jr_cc_taken() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC001;
+        cpu.registers.f.zero = true;
+        mmu.poke(0xC001, 0x10);
+
+        let cycles = jr_cc(&mut cpu, &mut mmu, Condition::Z);
+
+        assert_eq!(cycles, 12);
+        assert_eq!(cpu.registers.pc, 0xC012);
+    }
+
+    #[test]
+    fn test_jp_cc() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC001;
+        mmu.poke(0xC001, 0x34);
+        mmu.poke(0xC002, 0x12);
+
+        let cycles = jp_cc(&mut cpu, &mut mmu, Condition::C);
+        assert_eq!(cycles, 12);
+        assert_eq!(cpu.registers.pc, 0xC003);
+
+        cpu.registers.pc = 0xC001;
+        let cycles = jp_cc(&mut cpu, &mut mmu, Condition::NC);
+        assert_eq!(cycles, 16);
+        assert_eq!(cpu.registers.pc, 0x1234);
+    }
+
+    #[test]
+    fn test_call_and_ret() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC001;
+        cpu.registers.sp = 0xDFFE;
+        mmu.poke(0xC001, 0x34);
+        mmu.poke(0xC002, 0x12);
+
+        let cycles = call(&mut cpu, &mut mmu);
+
+        assert_eq!(cycles, 24);
+        assert_eq!(cpu.registers.pc, 0x1234);
+        assert_eq!(cpu.registers.sp, 0xDFFC);
+        assert_eq!(mmu.peek(0xDFFD), 0xC0);
+        assert_eq!(mmu.peek(0xDFFC), 0x03);
+
+        let cycles = ret(&mut cpu, &mut mmu);
+
+        assert_eq!(cycles, 16);
+        assert_eq!(cpu.registers.pc, 0xC003);
+        assert_eq!(cpu.registers.sp, 0xDFFE);
+    }
+
+    #[test]
+    fn test_call_cc_not_taken() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC001;
+        cpu.registers.sp = 0xDFFE;
+
+        let cycles = call_cc(&mut cpu, &mut mmu, Condition::Z);
+
+        assert_eq!(cycles, 12);
+        assert_eq!(cpu.registers.pc, 0xC003);
+        assert_eq!(cpu.registers.sp, 0xDFFE);
+    }
+
+    #[test]
+    fn test_ret_cc() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.sp = 0xDFFC;
+        mmu.poke(0xDFFC, 0x34);
+        mmu.poke(0xDFFD, 0x12);
+
+        assert_eq!(ret_cc(&mut cpu, &mut mmu, Condition::C), 8);
+        assert_eq!(cpu.registers.sp, 0xDFFC);
+
+        assert_eq!(ret_cc(&mut cpu, &mut mmu, Condition::NC), 20);
+        assert_eq!(cpu.registers.pc, 0x1234);
+        assert_eq!(cpu.registers.sp, 0xDFFE);
+    }
+
+    #[test]
+    fn test_reti_enables_interrupts() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.sp = 0xDFFC;
+        mmu.poke(0xDFFC, 0x34);
+        mmu.poke(0xDFFD, 0x12);
+
+        let cycles = reti(&mut cpu, &mut mmu);
+
+        assert_eq!(cycles, 16);
+        assert_eq!(cpu.registers.pc, 0x1234);
+        assert!(cpu.ime);
+    }
+
+    #[test]
+    fn test_rst() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC001;
+        cpu.registers.sp = 0xDFFE;
+
+        let cycles = rst(&mut cpu, &mut mmu, 0x38);
+
+        assert_eq!(cycles, 16);
+        assert_eq!(cpu.registers.pc, 0x0038);
+        assert_eq!(mmu.peek(0xDFFD), 0xC0);
+        assert_eq!(mmu.peek(0xDFFC), 0x01);
+    }
+
+    #[test]
+    fn test_rlc_sets_carry_from_bit_7() {
+        let mut cpu = Cpu::new();
+        cpu.registers.b = 0x85;
+
+        let cycles = cb_reg(&mut cpu, Reg::B, rlc);
+
+        assert_eq!(cycles, 8);
+        assert_eq!(cpu.registers.b, 0x0B);
+        assert_eq!(flags(&cpu), (false, false, false, true));
+    }
+
+    #[test]
+    fn test_rrc_zero() {
+        let mut cpu = Cpu::new();
+        cpu.registers.c = 0x00;
+        cpu.registers.f.carry = true;
+
+        cb_reg(&mut cpu, Reg::C, rrc);
+
+        assert_eq!(cpu.registers.c, 0x00);
+        assert_eq!(flags(&cpu), (true, false, false, false));
+    }
+
+    #[test]
+    fn test_rl_through_carry() {
+        let mut cpu = Cpu::new();
+        cpu.registers.l = 0x80;
+
+        cb_reg(&mut cpu, Reg::L, rl);
+
+        assert_eq!(cpu.registers.l, 0x00);
+        assert_eq!(flags(&cpu), (true, false, false, true));
+    }
+
+    #[test]
+    fn test_rr_addr_hl() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.f.carry = true;
+        cpu.registers.write_hl(0xC000);
+        mmu.poke(0xC000, 0x8A);
+
+        let cycles = cb_addr_hl(&mut cpu, &mut mmu, rr);
+
+        assert_eq!(cycles, 16);
+        assert_eq!(mmu.peek(0xC000), 0xC5);
+        assert_eq!(flags(&cpu), (false, false, false, false));
+    }
+
+    #[test]
+    fn test_sla() {
+        let mut cpu = Cpu::new();
+        cpu.registers.d = 0xFF;
+
+        cb_reg(&mut cpu, Reg::D, sla);
+
+        assert_eq!(cpu.registers.d, 0xFE);
+        assert_eq!(flags(&cpu), (false, false, false, true));
+    }
+
+    #[test]
+    fn test_sra_keeps_sign_bit() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x8A;
+
+        cb_reg(&mut cpu, Reg::A, sra);
+
+        assert_eq!(cpu.registers.a, 0xC5);
+        assert_eq!(flags(&cpu), (false, false, false, false));
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut cpu = Cpu::new();
+        cpu.registers.e = 0xF0;
+        cpu.registers.f.carry = true;
+
+        cb_reg(&mut cpu, Reg::E, swap);
+
+        assert_eq!(cpu.registers.e, 0x0F);
+        assert_eq!(flags(&cpu), (false, false, false, false));
+    }
+
+    #[test]
+    fn test_srl() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x01;
+
+        cb_reg(&mut cpu, Reg::A, srl);
+
+        assert_eq!(cpu.registers.a, 0x00);
+        assert_eq!(flags(&cpu), (true, false, false, true));
+    }
+
+    #[test]
+    fn test_bit_preserves_carry() {
+        let mut cpu = Cpu::new();
+        cpu.registers.h = 0x80;
+        cpu.registers.f.carry = true;
+
+        assert_eq!(bit(&mut cpu, 7, Reg::H), 8);
+        assert_eq!(flags(&cpu), (false, false, true, true));
+
+        bit(&mut cpu, 6, Reg::H);
+        assert_eq!(flags(&cpu), (true, false, true, true));
+    }
+
+    #[test]
+    fn test_bit_addr_hl() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.write_hl(0xC000);
+        mmu.poke(0xC000, 0x10);
+
+        let cycles = bit_addr_hl(&mut cpu, &mut mmu, 4);
+
+        assert_eq!(cycles, 12);
+        assert!(!cpu.registers.f.zero);
+    }
+
+    #[test]
+    fn test_res_and_set() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.b = 0xFF;
+        cpu.registers.write_hl(0xC000);
+
+        res(&mut cpu, 3, Reg::B);
+        let cycles = set_addr_hl(&mut cpu, &mut mmu, 7);
+
+        assert_eq!(cpu.registers.b, 0xF7);
+        assert_eq!(cycles, 16);
+        assert_eq!(mmu.peek(0xC000), 0x80);
+    }
+
+    #[test]
+    fn test_di_cancels_pending_ei() {
+        let mut cpu = Cpu::new();
+
+        ei(&mut cpu);
+        di(&mut cpu);
+
+        assert!(!cpu.ime);
+        assert!(!cpu.ime_scheduled);
+    }
+
+    #[test]
+    fn test_ld_imm16_advances_pc() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC000;
+        mmu.poke(0xC000, 0xFE);
+        mmu.poke(0xC001, 0xFF);
+
+        ld_imm16(&mut cpu, &mut mmu, Reg16::SP);
+
+        assert_eq!(cpu.registers.sp, 0xFFFE);
+        assert_eq!(cpu.registers.pc, 0xC002);
+    }
+
+    #[test]
+    fn test_inc16_and_dec16_wrap() {
+        let mut cpu = Cpu::new();
+        cpu.registers.write_bc(0xFFFF);
+        cpu.registers.f.zero = true;
+
+        assert_eq!(inc16(&mut cpu, Reg16::BC), 8);
+        assert_eq!(cpu.registers.read_bc(), 0x0000);
+        assert!(cpu.registers.f.zero);
+
+        dec16(&mut cpu, Reg16::SP);
+        assert_eq!(cpu.registers.sp, 0xFFFF);
+    }
+
+    #[test]
+    fn test_add_hl_r16_half_carry_from_bit_11() {
+        let mut cpu = Cpu::new();
+        cpu.registers.write_hl(0x8A23);
+        cpu.registers.write_bc(0x0605);
+        cpu.registers.f.zero = true;
+
+        let cycles = add_hl_r16(&mut cpu, Reg16::BC);
+
+        assert_eq!(cycles, 8);
+        assert_eq!(cpu.registers.read_hl(), 0x9028);
+        assert_eq!(flags(&cpu), (true, false, true, false));
+    }
+
+    #[test]
+    fn test_add_hl_hl_carry() {
+        let mut cpu = Cpu::new();
+        cpu.registers.write_hl(0x8A23);
+
+        add_hl_r16(&mut cpu, Reg16::HL);
+
+        assert_eq!(cpu.registers.read_hl(), 0x1446);
+        assert_eq!(flags(&cpu), (false, false, true, true));
+    }
+
+    #[test]
+    fn test_add_sp_imm8_negative_offset() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC000;
+        cpu.registers.sp = 0x00FF;
+        cpu.registers.f.zero = true;
+        mmu.poke(0xC000, 0xFF); // -1
+
+        let cycles = add_sp_imm8(&mut cpu, &mut mmu);
+
+        assert_eq!(cycles, 16);
+        assert_eq!(cpu.registers.sp, 0x00FE);
+        assert_eq!(flags(&cpu), (false, false, true, true));
+    }
+
+    #[test]
+    fn test_ld_hl_sp_imm8() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.pc = 0xC000;
+        cpu.registers.sp = 0xFFF8;
+        mmu.poke(0xC000, 0x02);
+
+        let cycles = ld_hl_sp_imm8(&mut cpu, &mut mmu);
+
+        assert_eq!(cycles, 12);
+        assert_eq!(cpu.registers.read_hl(), 0xFFFA);
+        assert_eq!(cpu.registers.sp, 0xFFF8);
+        assert_eq!(flags(&cpu), (false, false, false, false));
+    }
+
+    #[test]
+    fn test_ld_sp_hl() {
+        let mut cpu = Cpu::new();
+        cpu.registers.write_hl(0xC123);
+
+        assert_eq!(ld_sp_hl(&mut cpu), 8);
+        assert_eq!(cpu.registers.sp, 0xC123);
+    }
+
+    #[test]
+    fn test_push_and_pop() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.sp = 0xDFFE;
+        cpu.registers.write_de(0x1234);
+
+        assert_eq!(push(&mut cpu, &mut mmu, Reg16::DE), 16);
+        assert_eq!(cpu.registers.sp, 0xDFFC);
+
+        assert_eq!(pop(&mut cpu, &mut mmu, Reg16::BC), 12);
+        assert_eq!(cpu.registers.read_bc(), 0x1234);
+        assert_eq!(cpu.registers.sp, 0xDFFE);
+    }
+
+    #[test]
+    fn test_pop_af_clears_low_nibble() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.sp = 0xDFFC;
+        mmu.poke(0xDFFC, 0xFF);
+        mmu.poke(0xDFFD, 0x12);
+
+        pop(&mut cpu, &mut mmu, Reg16::AF);
+
+        assert_eq!(cpu.registers.a, 0x12);
+        assert_eq!(cpu.registers.read_af(), 0x12F0);
+        assert_eq!(flags(&cpu), (true, true, true, true));
+    }
+
+    #[test]
+    fn test_inc_preserves_carry() {
+        let mut cpu = Cpu::new();
+        cpu.registers.b = 0xFF;
+        cpu.registers.f.carry = true;
+
+        let cycles = inc(&mut cpu, Reg::B);
+
+        assert_eq!(cycles, 4);
+        assert_eq!(cpu.registers.b, 0x00);
+        assert_eq!(flags(&cpu), (true, false, true, true));
+    }
+
+    #[test]
+    fn test_inc_addr_hl() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.write_hl(0xC000);
+        mmu.poke(0xC000, 0x50);
+
+        let cycles = inc_addr_hl(&mut cpu, &mut mmu);
+
+        assert_eq!(cycles, 12);
+        assert_eq!(mmu.peek(0xC000), 0x51);
+        assert_eq!(flags(&cpu), (false, false, false, false));
+    }
+
+    #[test]
+    fn test_dec_half_borrow_preserves_carry() {
+        let mut cpu = Cpu::new();
+        cpu.registers.l = 0x10;
+        cpu.registers.f.carry = true;
+
+        let cycles = dec(&mut cpu, Reg::L);
+
+        assert_eq!(cycles, 4);
+        assert_eq!(cpu.registers.l, 0x0F);
+        assert_eq!(flags(&cpu), (false, true, true, true));
+    }
+
+    #[test]
+    fn test_dec_addr_hl_to_zero() {
+        let mut cpu = Cpu::new();
+        let mut mmu = Mmu::new();
+        cpu.registers.write_hl(0xC000);
+        mmu.poke(0xC000, 0x01);
+
+        let cycles = dec_addr_hl(&mut cpu, &mut mmu);
+
+        assert_eq!(cycles, 12);
+        assert_eq!(mmu.peek(0xC000), 0x00);
+        assert_eq!(flags(&cpu), (true, true, false, false));
+    }
+
+    #[test]
+    fn test_daa_after_add() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x45;
+        cpu.registers.b = 0x38;
+
+        add(&mut cpu, Reg::B);
+        daa(&mut cpu);
+
+        assert_eq!(cpu.registers.a, 0x83);
+        assert_eq!(flags(&cpu), (false, false, false, false));
+    }
+
+    #[test]
+    fn test_daa_after_add_with_carry_out() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x99;
+        cpu.registers.b = 0x01;
+
+        add(&mut cpu, Reg::B);
+        daa(&mut cpu);
+
+        assert_eq!(cpu.registers.a, 0x00);
+        assert_eq!(flags(&cpu), (true, false, false, true));
+    }
+
+    #[test]
+    fn test_daa_after_sub() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x83;
+        cpu.registers.b = 0x38;
+
+        sub(&mut cpu, Reg::B);
+        daa(&mut cpu);
+
+        assert_eq!(cpu.registers.a, 0x45);
+        assert_eq!(flags(&cpu), (false, true, false, false));
+    }
+
+    #[test]
+    fn test_daa_after_sub_with_borrow() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x10;
+        cpu.registers.b = 0x20;
+
+        sub(&mut cpu, Reg::B);
+        daa(&mut cpu);
+
+        assert_eq!(cpu.registers.a, 0x90);
+        assert_eq!(flags(&cpu), (false, true, false, true));
+    }
+
+    #[test]
+    fn test_cpl() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x35;
+
+        cpl(&mut cpu);
+
+        assert_eq!(cpu.registers.a, 0xCA);
+        assert_eq!(flags(&cpu), (false, true, true, false));
+    }
+
+    #[test]
+    fn test_scf() {
+        let mut cpu = Cpu::new();
+        cpu.registers.f.subtract = true;
+        cpu.registers.f.half_carry = true;
+        cpu.registers.f.zero = true;
+
+        scf(&mut cpu);
+
+        assert_eq!(flags(&cpu), (true, false, false, true));
+    }
+
+    #[test]
+    fn test_ccf() {
+        let mut cpu = Cpu::new();
+        cpu.registers.f.carry = true;
+        cpu.registers.f.half_carry = true;
+
+        ccf(&mut cpu);
+        assert_eq!(flags(&cpu), (false, false, false, false));
+
+        ccf(&mut cpu);
+        assert_eq!(flags(&cpu), (false, false, false, true));
+    }
+
+    #[test]
+    fn test_rlca_clears_zero() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x85;
+        cpu.registers.f.zero = true;
+
+        let cycles = rotate_a(&mut cpu, rlc);
+
+        assert_eq!(cycles, 4);
+        assert_eq!(cpu.registers.a, 0x0B);
+        assert_eq!(flags(&cpu), (false, false, false, true));
+    }
+
+    #[test]
+    fn test_rrca() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x3B;
+
+        rotate_a(&mut cpu, rrc);
+
+        assert_eq!(cpu.registers.a, 0x9D);
+        assert_eq!(flags(&cpu), (false, false, false, true));
+    }
+
+    #[test]
+    fn test_rla_zero_result_keeps_z_clear() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x80;
+
+        rotate_a(&mut cpu, rl);
+
+        assert_eq!(cpu.registers.a, 0x00);
+        assert_eq!(flags(&cpu), (false, false, false, true));
+    }
+
+    #[test]
+    fn test_rra() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x81;
+        cpu.registers.f.carry = false;
+
+        rotate_a(&mut cpu, rr);
+
+        assert_eq!(cpu.registers.a, 0x40);
+        assert_eq!(flags(&cpu), (false, false, false, true));
+    }
 }
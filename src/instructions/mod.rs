@@ -1,19 +1,20 @@
-use crate::{cpu::Cpu, mmu::Mmu};
+use crate::{bus::Bus, cpu::Cpu};
 
+mod cb_instruction_set;
 mod functions;
 mod instruction_set;
 
 pub struct Instruction {
     pub opcode: u8,
     pub mnemonic: &'static str,
-    pub execute: fn(&mut Cpu, &mut Mmu) -> u8,
+    pub execute: fn(&mut Cpu, &mut dyn Bus) -> u8,
 }
 
 impl Instruction {
     pub const fn new(
         opcode: u8,
         mnemonic: &'static str,
-        execute: fn(&mut Cpu, &mut Mmu) -> u8,
+        execute: fn(&mut Cpu, &mut dyn Bus) -> u8,
     ) -> Self {
         Instruction {
             opcode,
@@ -23,12 +24,14 @@ impl Instruction {
     }
 
     pub fn from_byte(byte: u8) -> Option<&'static Self> {
-        for inst in instruction_set::INSTRUCTIONS.iter() {
-            if inst.opcode == byte {
-                return Some(inst);
-            }
-        }
+        instruction_set::INSTRUCTIONS
+            .iter()
+            .find(|inst| inst.opcode == byte)
+    }
 
-        None
+    pub fn from_cb_byte(byte: u8) -> Option<&'static Self> {
+        cb_instruction_set::CB_INSTRUCTIONS
+            .iter()
+            .find(|inst| inst.opcode == byte)
     }
 }
@@ -0,0 +1,13 @@
+pub mod bus;
+pub mod cpu;
+pub mod gameboy;
+pub mod instructions;
+pub mod interrupts;
+pub mod mbc;
+pub mod mmu;
+pub mod ppu;
+pub mod registers;
+pub mod rom;
+pub mod save;
+pub mod timer;
+pub mod utils;
@@ -1,11 +1,3 @@
-mod cpu;
-mod instructions;
-mod mmu;
-mod registers;
-mod utils;
-
 fn main() {
     env_logger::init();
-
-    println!("Hello, World!")
 }
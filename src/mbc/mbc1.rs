@@ -0,0 +1,242 @@
+use crate::{
+    mbc::{Mbc, RAM_BANK_SIZE, ROM_BANK_SIZE},
+    rom::{LOGO_ADDR, NINTENDO_LOGO},
+};
+
+// MBC1M multicarts are 1 MiB and repeat the boot logo in every 256 KiB game
+const MULTICART_ROM_SIZE: usize = 0x100000;
+const MULTICART_GAME_SIZE: usize = 0x40000;
+
+pub struct Mbc1 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    // 5-bit BANK1 register, 0 is remapped to 1
+    rom_bank: u8,
+    // 2-bit BANK2 register, the upper ROM bits or the RAM bank
+    upper_bits: u8,
+    // 1 = BANK2 also applies to 0x0000-0x3FFF and RAM
+    advanced_mode: bool,
+    // MBC1M wires BANK2 to ROM address bits 18-19 instead of 19-20
+    multicart: bool,
+}
+
+impl Mbc1 {
+    pub fn new(rom: Vec<u8>, ram_size: usize) -> Self {
+        let multicart = is_multicart(&rom);
+
+        Mbc1 {
+            rom,
+            ram: vec![0; ram_size],
+            ram_enabled: false,
+            rom_bank: 1,
+            upper_bits: 0,
+            advanced_mode: false,
+            multicart,
+        }
+    }
+
+    fn upper_shift(&self) -> u32 {
+        if self.multicart { 4 } else { 5 }
+    }
+
+    fn rom_bank_low(&self) -> usize {
+        if self.advanced_mode {
+            (self.upper_bits as usize) << self.upper_shift()
+        } else {
+            0
+        }
+    }
+
+    fn rom_bank_high(&self) -> usize {
+        let bank = if self.multicart {
+            self.rom_bank & 0x0F
+        } else {
+            self.rom_bank
+        };
+
+        ((self.upper_bits as usize) << self.upper_shift()) | bank as usize
+    }
+
+    fn ram_offset(&self, addr: u16) -> usize {
+        let bank = if self.advanced_mode {
+            self.upper_bits as usize
+        } else {
+            0
+        };
+
+        (bank * RAM_BANK_SIZE + (addr - 0xA000) as usize) % self.ram.len()
+    }
+}
+
+impl Mbc for Mbc1 {
+    fn read_rom(&self, addr: u16) -> u8 {
+        let bank = match addr {
+            0x0000..=0x3FFF => self.rom_bank_low(),
+            _ => self.rom_bank_high(),
+        };
+        let offset = bank * ROM_BANK_SIZE + (addr as usize & (ROM_BANK_SIZE - 1));
+
+        // Bank numbers wrap around to the ROM size
+        self.rom[offset % self.rom.len()]
+    }
+
+    fn write_rom(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => {
+                self.rom_bank = match value & 0x1F {
+                    0 => 1,
+                    bank => bank,
+                }
+            }
+            0x4000..=0x5FFF => self.upper_bits = value & 0x03,
+            _ => self.advanced_mode = value & 0x01 != 0,
+        }
+    }
+
+    fn read_ram(&self, addr: u16) -> u8 {
+        if !self.ram_enabled || self.ram.is_empty() {
+            return 0xFF;
+        }
+
+        self.ram[self.ram_offset(addr)]
+    }
+
+    fn write_ram(&mut self, addr: u16, value: u8) -> bool {
+        if !self.ram_enabled || self.ram.is_empty() {
+            return false;
+        }
+
+        let offset = self.ram_offset(addr);
+        self.ram[offset] = value;
+        true
+    }
+
+    fn ram(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+}
+
+fn is_multicart(rom: &[u8]) -> bool {
+    if rom.len() != MULTICART_ROM_SIZE {
+        return false;
+    }
+
+    // The menu at bank 0x00 plus at least one game at 0x10, 0x20 or 0x30
+    let logo_count = (0..MULTICART_ROM_SIZE)
+        .step_by(MULTICART_GAME_SIZE)
+        .filter(|&base| {
+            let start = base + LOGO_ADDR;
+            rom[start..start + NINTENDO_LOGO.len()] == NINTENDO_LOGO
+        })
+        .count();
+
+    logo_count > 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mbc::tests::banked_rom;
+
+    #[test]
+    fn test_rom_bank_zero_maps_to_one() {
+        let mut mbc = Mbc1::new(banked_rom(8), 0);
+
+        assert_eq!(mbc.read_rom(0x4000), 1);
+
+        mbc.write_rom(0x2000, 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 1);
+
+        mbc.write_rom(0x2000, 0x05);
+        assert_eq!(mbc.read_rom(0x4000), 5);
+        assert_eq!(mbc.read_rom(0x0000), 0);
+    }
+
+    #[test]
+    fn test_rom_bank_wraps_to_rom_size() {
+        let mut mbc = Mbc1::new(banked_rom(4), 0);
+
+        mbc.write_rom(0x2000, 0x06);
+
+        assert_eq!(mbc.read_rom(0x4000), 2);
+    }
+
+    #[test]
+    fn test_upper_bits_select_high_rom_banks() {
+        let mut mbc = Mbc1::new(banked_rom(128), 0);
+
+        mbc.write_rom(0x2000, 0x02);
+        mbc.write_rom(0x4000, 0x01);
+        assert_eq!(mbc.read_rom(0x4000), 0x22);
+        assert_eq!(mbc.read_rom(0x0000), 0x00);
+
+        // Writing 0x20 to BANK1 cannot select bank 0x20, only 0x21
+        mbc.write_rom(0x2000, 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 0x21);
+
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(mbc.read_rom(0x0000), 0x20);
+    }
+
+    #[test]
+    fn test_ram_enable() {
+        let mut mbc = Mbc1::new(banked_rom(2), 0x2000);
+
+        mbc.write_ram(0xA000, 0x42);
+        assert_eq!(mbc.read_ram(0xA000), 0xFF);
+
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_ram(0xA000, 0x42);
+        assert_eq!(mbc.read_ram(0xA000), 0x42);
+
+        mbc.write_rom(0x0000, 0x00);
+        assert_eq!(mbc.read_ram(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn test_ram_banking_requires_advanced_mode() {
+        let mut mbc = Mbc1::new(banked_rom(2), 0x8000);
+        mbc.write_rom(0x0000, 0x0A);
+
+        mbc.write_rom(0x4000, 0x02);
+        mbc.write_ram(0xA000, 0x11);
+
+        mbc.write_rom(0x6000, 0x01);
+        mbc.write_ram(0xA000, 0x22);
+        assert_eq!(mbc.read_ram(0xA000), 0x22);
+
+        mbc.write_rom(0x6000, 0x00);
+        assert_eq!(mbc.read_ram(0xA000), 0x11);
+    }
+
+    #[test]
+    fn test_multicart_uses_four_bit_bank1() {
+        let mut rom = banked_rom(64);
+        for game in [0x00, 0x10, 0x20] {
+            let start = game * ROM_BANK_SIZE + LOGO_ADDR;
+            rom[start..start + NINTENDO_LOGO.len()].copy_from_slice(&NINTENDO_LOGO);
+        }
+        let mut mbc = Mbc1::new(rom, 0);
+
+        mbc.write_rom(0x4000, 0x02);
+        mbc.write_rom(0x2000, 0x13);
+        assert_eq!(mbc.read_rom(0x4000), 0x23);
+
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(mbc.read_rom(0x0000), 0x20);
+    }
+
+    #[test]
+    fn test_plain_1mib_rom_is_not_multicart() {
+        let mut rom = banked_rom(64);
+        rom[LOGO_ADDR..LOGO_ADDR + NINTENDO_LOGO.len()].copy_from_slice(&NINTENDO_LOGO);
+        let mut mbc = Mbc1::new(rom, 0);
+
+        mbc.write_rom(0x4000, 0x01);
+        mbc.write_rom(0x2000, 0x13);
+
+        assert_eq!(mbc.read_rom(0x4000), 0x33);
+    }
+}
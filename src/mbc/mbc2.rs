@@ -0,0 +1,145 @@
+use crate::mbc::{Mbc, ROM_BANK_SIZE};
+
+const RAM_SIZE: usize = 512;
+
+pub struct Mbc2 {
+    rom: Vec<u8>,
+    // 512 half-bytes built into the MBC, only the lower nibble is stored
+    ram: [u8; RAM_SIZE],
+    ram_enabled: bool,
+    // 4-bit ROM bank, 0 is remapped to 1
+    rom_bank: u8,
+}
+
+impl Mbc2 {
+    pub fn new(rom: Vec<u8>) -> Self {
+        Mbc2 {
+            rom,
+            ram: [0; RAM_SIZE],
+            ram_enabled: false,
+            rom_bank: 1,
+        }
+    }
+}
+
+impl Mbc for Mbc2 {
+    fn read_rom(&self, addr: u16) -> u8 {
+        let bank = match addr {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        };
+        let offset = bank * ROM_BANK_SIZE + (addr as usize & (ROM_BANK_SIZE - 1));
+
+        self.rom[offset % self.rom.len()]
+    }
+
+    fn write_rom(&mut self, addr: u16, value: u8) {
+        // Both registers live in 0x0000-0x3FFF, address bit 8 selects which
+        if addr > 0x3FFF {
+            return;
+        }
+
+        if addr & 0x0100 == 0 {
+            self.ram_enabled = value & 0x0F == 0x0A;
+        } else {
+            self.rom_bank = match value & 0x0F {
+                0 => 1,
+                bank => bank,
+            };
+        }
+    }
+
+    fn read_ram(&self, addr: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+
+        // Only 9 address lines are decoded, so the RAM echoes through
+        // 0xA000-0xBFFF; the upper nibble is open bus
+        0xF0 | self.ram[addr as usize & (RAM_SIZE - 1)]
+    }
+
+    fn write_ram(&mut self, addr: u16, value: u8) -> bool {
+        if !self.ram_enabled {
+            return false;
+        }
+
+        self.ram[addr as usize & (RAM_SIZE - 1)] = value & 0x0F;
+        true
+    }
+
+    fn ram(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mbc::tests::banked_rom;
+
+    #[test]
+    fn test_register_select_by_address_bit_8() {
+        let mut mbc = Mbc2::new(banked_rom(16));
+
+        // Bit 8 clear: RAM enable, the ROM bank is unaffected
+        mbc.write_rom(0x0000, 0x05);
+        assert_eq!(mbc.read_rom(0x4000), 1);
+
+        mbc.write_rom(0x2100, 0x05);
+        assert_eq!(mbc.read_rom(0x4000), 5);
+
+        mbc.write_rom(0x0100, 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 1);
+    }
+
+    #[test]
+    fn test_rom_bank_uses_four_bits() {
+        let mut mbc = Mbc2::new(banked_rom(16));
+
+        mbc.write_rom(0x2100, 0x1F);
+
+        assert_eq!(mbc.read_rom(0x4000), 0x0F);
+    }
+
+    #[test]
+    fn test_writes_above_0x3fff_are_ignored() {
+        let mut mbc = Mbc2::new(banked_rom(16));
+
+        mbc.write_rom(0x4100, 0x03);
+
+        assert_eq!(mbc.read_rom(0x4000), 1);
+    }
+
+    #[test]
+    fn test_ram_is_four_bits_with_open_bus_upper_nibble() {
+        let mut mbc = Mbc2::new(banked_rom(2));
+        mbc.write_rom(0x0000, 0x0A);
+
+        mbc.write_ram(0xA000, 0xAB);
+
+        assert_eq!(mbc.read_ram(0xA000), 0xFB);
+    }
+
+    #[test]
+    fn test_ram_echoes_across_window() {
+        let mut mbc = Mbc2::new(banked_rom(2));
+        mbc.write_rom(0x0000, 0x0A);
+
+        mbc.write_ram(0xA005, 0x07);
+
+        assert_eq!(mbc.read_ram(0xA205), 0xF7);
+        assert_eq!(mbc.read_ram(0xBE05), 0xF7);
+    }
+
+    #[test]
+    fn test_ram_disabled() {
+        let mut mbc = Mbc2::new(banked_rom(2));
+
+        mbc.write_ram(0xA000, 0x07);
+        assert_eq!(mbc.read_ram(0xA000), 0xFF);
+
+        mbc.write_rom(0x0000, 0x0A);
+        assert_eq!(mbc.read_ram(0xA000), 0xF0);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::mbc::{
+    Mbc, RAM_BANK_SIZE, ROM_BANK_SIZE,
+    rtc::{Clock, RTC_FOOTER_SIZE, Rtc},
+};
+
+pub struct Mbc3 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    rtc: Option<Rtc>,
+    // Enables both RAM and RTC register access
+    ram_enabled: bool,
+    // 7-bit ROM bank, 0 is remapped to 1
+    rom_bank: u8,
+    // 0x00-0x07 selects a RAM bank, 0x08-0x0C an RTC register
+    ram_select: u8,
+    // Latching happens on a 0x00 -> 0x01 write sequence
+    last_latch_write: u8,
+}
+
+impl Mbc3 {
+    pub fn new(rom: Vec<u8>, ram_size: usize, clock: Option<Box<dyn Clock>>) -> Self {
+        Mbc3 {
+            rom,
+            ram: vec![0; ram_size],
+            rtc: clock.map(Rtc::new),
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_select: 0,
+            last_latch_write: 0xFF,
+        }
+    }
+
+    fn ram_offset(&self, addr: u16) -> Option<usize> {
+        if self.ram.is_empty() {
+            return None;
+        }
+
+        let offset = self.ram_select as usize * RAM_BANK_SIZE + (addr - 0xA000) as usize;
+        Some(offset % self.ram.len())
+    }
+}
+
+impl Mbc for Mbc3 {
+    fn read_rom(&self, addr: u16) -> u8 {
+        let bank = match addr {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        };
+        let offset = bank * ROM_BANK_SIZE + (addr as usize & (ROM_BANK_SIZE - 1));
+
+        self.rom[offset % self.rom.len()]
+    }
+
+    fn write_rom(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => {
+                self.rom_bank = match value & 0x7F {
+                    0 => 1,
+                    bank => bank,
+                }
+            }
+            0x4000..=0x5FFF => self.ram_select = value & 0x0F,
+            _ => {
+                if self.last_latch_write == 0x00
+                    && value == 0x01
+                    && let Some(rtc) = &mut self.rtc
+                {
+                    rtc.latch();
+                }
+                self.last_latch_write = value;
+            }
+        }
+    }
+
+    fn read_ram(&self, addr: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+
+        match (self.ram_select, &self.rtc) {
+            (0x00..=0x07, _) => self
+                .ram_offset(addr)
+                .map_or(0xFF, |offset| self.ram[offset]),
+            (0x08..=0x0C, Some(rtc)) => rtc.read(self.ram_select),
+            _ => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, addr: u16, value: u8) -> bool {
+        if !self.ram_enabled {
+            return false;
+        }
+
+        // RTC registers are part of the save file too
+        match (self.ram_select, &mut self.rtc) {
+            (0x00..=0x07, _) => match self.ram_offset(addr) {
+                Some(offset) => {
+                    self.ram[offset] = value;
+                    true
+                }
+                None => false,
+            },
+            (0x08..=0x0C, Some(rtc)) => {
+                rtc.write(self.ram_select, value);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn ram(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    // The RTC registers follow RAM in the 48-byte footer other emulators use
+    fn save_data(&mut self) -> Vec<u8> {
+        let mut data = self.ram.clone();
+        if let Some(rtc) = &mut self.rtc {
+            data.extend_from_slice(&rtc.save());
+        }
+
+        data
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        let ram_len = self.ram.len().min(data.len());
+        self.ram[..ram_len].copy_from_slice(&data[..ram_len]);
+
+        if let Some(rtc) = &mut self.rtc
+            && !rtc.load(&data[ram_len..])
+            && data.len() > ram_len
+        {
+            log::warn!(
+                "ignoring RTC footer of {} bytes, expected {RTC_FOOTER_SIZE}",
+                data.len() - ram_len
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mbc::rtc::tests::FakeClock;
+    use crate::mbc::tests::banked_rom;
+
+    #[test]
+    fn test_rom_banking() {
+        let mut mbc = Mbc3::new(banked_rom(128), 0, None);
+
+        assert_eq!(mbc.read_rom(0x4000), 1);
+
+        mbc.write_rom(0x2000, 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 1);
+
+        mbc.write_rom(0x2000, 0x7F);
+        assert_eq!(mbc.read_rom(0x4000), 0x7F);
+        assert_eq!(mbc.read_rom(0x0000), 0x00);
+    }
+
+    #[test]
+    fn test_ram_banking() {
+        let mut mbc = Mbc3::new(banked_rom(2), 0x8000, None);
+        mbc.write_rom(0x0000, 0x0A);
+
+        mbc.write_rom(0x4000, 0x01);
+        mbc.write_ram(0xA000, 0x11);
+        mbc.write_rom(0x4000, 0x03);
+        mbc.write_ram(0xA000, 0x33);
+
+        mbc.write_rom(0x4000, 0x01);
+        assert_eq!(mbc.read_ram(0xA000), 0x11);
+        mbc.write_rom(0x4000, 0x03);
+        assert_eq!(mbc.read_ram(0xA000), 0x33);
+    }
+
+    #[test]
+    fn test_rtc_requires_latch_sequence() {
+        let clock = FakeClock::new(0);
+        let mut mbc = Mbc3::new(banked_rom(2), 0x2000, Some(Box::new(clock.clone())));
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x4000, 0x08);
+
+        clock.advance(42);
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(mbc.read_ram(0xA000), 0);
+
+        mbc.write_rom(0x6000, 0x00);
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(mbc.read_ram(0xA000), 42);
+    }
+
+    #[test]
+    fn test_rtc_fast_forward_days() {
+        let clock = FakeClock::new(0);
+        let mut mbc = Mbc3::new(banked_rom(2), 0, Some(Box::new(clock.clone())));
+        mbc.write_rom(0x0000, 0x0A);
+
+        clock.advance(300 * 86400 + 3600);
+        mbc.write_rom(0x6000, 0x00);
+        mbc.write_rom(0x6000, 0x01);
+
+        mbc.write_rom(0x4000, 0x0A);
+        assert_eq!(mbc.read_ram(0xA000), 1);
+        mbc.write_rom(0x4000, 0x0B);
+        assert_eq!(mbc.read_ram(0xA000), (300 & 0xFF) as u8);
+        mbc.write_rom(0x4000, 0x0C);
+        assert_eq!(mbc.read_ram(0xA000), 0x01);
+    }
+
+    #[test]
+    fn test_rtc_without_timer_reads_open_bus() {
+        let mut mbc = Mbc3::new(banked_rom(2), 0x2000, None);
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x4000, 0x08);
+
+        assert_eq!(mbc.read_ram(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn test_save_data_round_trip() {
+        let clock = FakeClock::new(1_000_000);
+        let mut mbc = Mbc3::new(banked_rom(2), 0x2000, Some(Box::new(clock.clone())));
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_ram(0xA010, 0x99);
+        mbc.write_rom(0x4000, 0x09);
+        mbc.write_ram(0xA000, 30);
+
+        let data = mbc.save_data();
+        assert_eq!(data.len(), 0x2000 + RTC_FOOTER_SIZE);
+
+        let mut restored = Mbc3::new(banked_rom(2), 0x2000, Some(Box::new(clock.clone())));
+        restored.load_save_data(&data);
+        clock.advance(120);
+        restored.write_rom(0x0000, 0x0A);
+        restored.write_rom(0x6000, 0x00);
+        restored.write_rom(0x6000, 0x01);
+
+        assert_eq!(restored.read_ram(0xA010), 0x99);
+        restored.write_rom(0x4000, 0x09);
+        assert_eq!(restored.read_ram(0xA000), 32);
+    }
+}
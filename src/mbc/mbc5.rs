@@ -0,0 +1,173 @@
+use crate::mbc::{Mbc, RAM_BANK_SIZE, ROM_BANK_SIZE};
+
+pub struct Mbc5 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    // 9-bit ROM bank, unlike MBC1/MBC3 bank 0 can be mapped at 0x4000
+    rom_bank: u16,
+    ram_bank: u8,
+    // On rumble carts bit 3 of the RAM bank register drives the motor
+    rumble: bool,
+    motor_on: bool,
+    on_rumble: Option<Box<dyn FnMut(bool)>>,
+}
+
+impl Mbc5 {
+    pub fn new(rom: Vec<u8>, ram_size: usize, rumble: bool) -> Self {
+        Mbc5 {
+            rom,
+            ram: vec![0; ram_size],
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            rumble,
+            motor_on: false,
+            on_rumble: None,
+        }
+    }
+
+    fn ram_offset(&self, addr: u16) -> Option<usize> {
+        if !self.ram_enabled || self.ram.is_empty() {
+            return None;
+        }
+
+        let offset = self.ram_bank as usize * RAM_BANK_SIZE + (addr - 0xA000) as usize;
+        Some(offset % self.ram.len())
+    }
+
+    fn set_motor(&mut self, on: bool) {
+        if on == self.motor_on {
+            return;
+        }
+
+        self.motor_on = on;
+        if let Some(callback) = &mut self.on_rumble {
+            callback(on);
+        }
+    }
+}
+
+impl Mbc for Mbc5 {
+    fn read_rom(&self, addr: u16) -> u8 {
+        let bank = match addr {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        };
+        let offset = bank * ROM_BANK_SIZE + (addr as usize & (ROM_BANK_SIZE - 1));
+
+        self.rom[offset % self.rom.len()]
+    }
+
+    fn write_rom(&mut self, addr: u16, value: u8) {
+        match addr {
+            // MBC5 compares the whole byte, not just the lower nibble
+            0x0000..=0x1FFF => self.ram_enabled = value == 0x0A,
+            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x100) | value as u16,
+            0x3000..=0x3FFF => {
+                self.rom_bank = (self.rom_bank & 0xFF) | ((value as u16 & 0x01) << 8)
+            }
+            0x4000..=0x5FFF => {
+                if self.rumble {
+                    self.ram_bank = value & 0x07;
+                    self.set_motor(value & 0x08 != 0);
+                } else {
+                    self.ram_bank = value & 0x0F;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn read_ram(&self, addr: u16) -> u8 {
+        self.ram_offset(addr)
+            .map_or(0xFF, |offset| self.ram[offset])
+    }
+
+    fn write_ram(&mut self, addr: u16, value: u8) -> bool {
+        let Some(offset) = self.ram_offset(addr) else {
+            return false;
+        };
+
+        self.ram[offset] = value;
+        true
+    }
+
+    fn ram(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn set_rumble_callback(&mut self, callback: Box<dyn FnMut(bool)>) {
+        self.on_rumble = Some(callback);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mbc::tests::banked_rom;
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn test_nine_bit_rom_bank() {
+        let mut mbc = Mbc5::new(banked_rom(512), 0, false);
+
+        mbc.write_rom(0x2000, 0x23);
+        mbc.write_rom(0x3000, 0x01);
+        assert_eq!(mbc.read_rom(0x4000), 0x23);
+        assert_eq!(mbc.read_rom(0x4001), 0x01);
+
+        mbc.write_rom(0x3000, 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 0x23);
+        assert_eq!(mbc.read_rom(0x4001), 0x00);
+    }
+
+    #[test]
+    fn test_rom_bank_zero_is_selectable() {
+        let mut mbc = Mbc5::new(banked_rom(4), 0, false);
+        mbc.write_rom(0x2000, 0x02);
+
+        mbc.write_rom(0x2000, 0x00);
+
+        assert_eq!(mbc.read_rom(0x4000), 0x00);
+    }
+
+    #[test]
+    fn test_sixteen_ram_banks() {
+        let mut mbc = Mbc5::new(banked_rom(2), 0x20000, false);
+        mbc.write_rom(0x0000, 0x0A);
+
+        mbc.write_rom(0x4000, 0x0F);
+        mbc.write_ram(0xA000, 0xF0);
+        mbc.write_rom(0x4000, 0x00);
+        mbc.write_ram(0xA000, 0x00);
+
+        mbc.write_rom(0x4000, 0x0F);
+        assert_eq!(mbc.read_ram(0xA000), 0xF0);
+    }
+
+    #[test]
+    fn test_ram_enable_needs_exact_value() {
+        let mut mbc = Mbc5::new(banked_rom(2), 0x2000, false);
+
+        mbc.write_rom(0x0000, 0x1A);
+        mbc.write_ram(0xA000, 0x42);
+
+        assert_eq!(mbc.read_ram(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn test_rumble_callback_fires_on_change() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut mbc = Mbc5::new(banked_rom(2), 0x8000, true);
+        let sink = events.clone();
+        mbc.set_rumble_callback(Box::new(move |on| sink.borrow_mut().push(on)));
+
+        mbc.write_rom(0x4000, 0x08);
+        mbc.write_rom(0x4000, 0x09);
+        mbc.write_rom(0x4000, 0x01);
+
+        assert_eq!(*events.borrow(), vec![true, false]);
+        assert_eq!(mbc.ram_bank, 0x01);
+    }
+}
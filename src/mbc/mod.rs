@@ -0,0 +1,96 @@
+use crate::rom::{Cartridge, CartridgeError, Mbc as MbcKind};
+
+mod mbc1;
+mod mbc2;
+mod mbc3;
+mod mbc5;
+mod rom_only;
+pub mod rtc;
+
+pub use mbc1::Mbc1;
+pub use mbc2::Mbc2;
+pub use mbc3::Mbc3;
+pub use mbc5::Mbc5;
+pub use rom_only::RomOnly;
+use rtc::{Clock, SystemClock};
+
+pub const ROM_BANK_SIZE: usize = 0x4000;
+pub const RAM_BANK_SIZE: usize = 0x2000;
+
+/// A cartridge mapper. `Mmu` routes 0x0000-0x7FFF to the ROM methods and
+/// 0xA000-0xBFFF to the RAM methods; writes to ROM addresses program the
+/// mapper registers.
+pub trait Mbc {
+    fn read_rom(&self, addr: u16) -> u8;
+    fn write_rom(&mut self, addr: u16, value: u8);
+    fn read_ram(&self, addr: u16) -> u8;
+    /// Returns whether the write reached memory that is saved, false while
+    /// RAM is disabled or absent.
+    fn write_ram(&mut self, addr: u16, value: u8) -> bool;
+
+    /// Cartridge RAM, empty when the cartridge has none.
+    fn ram(&mut self) -> &mut [u8] {
+        &mut []
+    }
+
+    /// Contents of the battery-backed memory as stored in a save file.
+    fn save_data(&mut self) -> Vec<u8> {
+        self.ram().to_vec()
+    }
+
+    /// Restores RAM from a save file, ignoring bytes past its end.
+    fn load_save_data(&mut self, data: &[u8]) {
+        let ram = self.ram();
+        let len = ram.len().min(data.len());
+        ram[..len].copy_from_slice(&data[..len]);
+    }
+
+    /// Called with the new motor state whenever a rumble cartridge toggles it.
+    fn set_rumble_callback(&mut self, _callback: Box<dyn FnMut(bool)>) {}
+}
+
+pub fn from_cartridge(cartridge: Cartridge) -> Result<Box<dyn Mbc>, CartridgeError> {
+    from_cartridge_with_clock(cartridge, Box::new(SystemClock))
+}
+
+/// Like `from_cartridge`, with the time source used by cartridges that have
+/// a real-time clock.
+pub fn from_cartridge_with_clock(
+    cartridge: Cartridge,
+    clock: Box<dyn Clock>,
+) -> Result<Box<dyn Mbc>, CartridgeError> {
+    let header = &cartridge.header;
+    let ram_size = header.ram_size;
+
+    match header.cartridge_type.mbc {
+        MbcKind::None => Ok(Box::new(RomOnly::new(cartridge.rom, ram_size))),
+        MbcKind::Mbc1 => Ok(Box::new(Mbc1::new(cartridge.rom, ram_size))),
+        MbcKind::Mbc2 => Ok(Box::new(Mbc2::new(cartridge.rom))),
+        MbcKind::Mbc3 => {
+            let clock = header.cartridge_type.timer.then_some(clock);
+            Ok(Box::new(Mbc3::new(cartridge.rom, ram_size, clock)))
+        }
+        MbcKind::Mbc5 => {
+            let rumble = header.cartridge_type.rumble;
+            Ok(Box::new(Mbc5::new(cartridge.rom, ram_size, rumble)))
+        }
+        mbc => Err(CartridgeError::UnsupportedMbc(mbc)),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::ROM_BANK_SIZE;
+
+    /// A ROM whose banks start with their own 9-bit bank number, low byte
+    /// first.
+    pub fn banked_rom(banks: usize) -> Vec<u8> {
+        let mut rom = vec![0; banks * ROM_BANK_SIZE];
+        for bank in 0..banks {
+            rom[bank * ROM_BANK_SIZE] = bank as u8;
+            rom[bank * ROM_BANK_SIZE + 1] = (bank >> 8) as u8;
+        }
+
+        rom
+    }
+}
@@ -0,0 +1,281 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Size of the RTC footer appended to the cartridge RAM in save files, in
+/// the layout used by BGB and VBA-M: current and latched registers as ten
+/// little-endian u32s followed by a little-endian u64 UNIX timestamp.
+pub const RTC_FOOTER_SIZE: usize = 48;
+// Older saves store the timestamp as a u32
+const RTC_FOOTER_SIZE_32: usize = 44;
+
+const DAY_COUNTER_LIMIT: u64 = 512;
+
+/// Source of wall-clock time for the RTC, in seconds since the UNIX epoch.
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+struct RtcRegisters {
+    seconds: u8,
+    minutes: u8,
+    hours: u8,
+    days: u16,
+    halt: bool,
+    carry: bool,
+}
+
+impl RtcRegisters {
+    fn read(&self, reg: u8) -> u8 {
+        match reg {
+            0x08 => self.seconds,
+            0x09 => self.minutes,
+            0x0A => self.hours,
+            0x0B => self.days as u8,
+            _ => (self.carry as u8) << 7 | (self.halt as u8) << 6 | (self.days >> 8) as u8,
+        }
+    }
+
+    fn write(&mut self, reg: u8, value: u8) {
+        match reg {
+            0x08 => self.seconds = value & 0x3F,
+            0x09 => self.minutes = value & 0x3F,
+            0x0A => self.hours = value & 0x1F,
+            0x0B => self.days = (self.days & 0x100) | value as u16,
+            _ => {
+                self.days = (self.days & 0xFF) | ((value as u16 & 0x01) << 8);
+                self.halt = value & 0x40 != 0;
+                self.carry = value & 0x80 != 0;
+            }
+        }
+    }
+
+    fn advance(&mut self, elapsed: u64) {
+        let total = self.seconds as u64
+            + self.minutes as u64 * 60
+            + self.hours as u64 * 3600
+            + self.days as u64 * 86400
+            + elapsed;
+
+        let days = total / 86400;
+        if days >= DAY_COUNTER_LIMIT {
+            self.carry = true;
+        }
+
+        self.seconds = (total % 60) as u8;
+        self.minutes = (total / 60 % 60) as u8;
+        self.hours = (total / 3600 % 24) as u8;
+        self.days = (days % DAY_COUNTER_LIMIT) as u16;
+    }
+
+    fn to_footer(self) -> [u32; 5] {
+        [
+            self.seconds as u32,
+            self.minutes as u32,
+            self.hours as u32,
+            self.read(0x0B) as u32,
+            self.read(0x0C) as u32,
+        ]
+    }
+
+    fn from_footer(values: [u32; 5]) -> Self {
+        let mut regs = RtcRegisters::default();
+        for (reg, value) in (0x08..=0x0C).zip(values) {
+            regs.write(reg, value as u8);
+        }
+
+        regs
+    }
+}
+
+/// The MBC3 real-time clock. Time is advanced lazily from the injected
+/// clock whenever the registers are latched or written.
+pub struct Rtc {
+    current: RtcRegisters,
+    latched: RtcRegisters,
+    last_update: u64,
+    clock: Box<dyn Clock>,
+}
+
+impl Rtc {
+    pub fn new(clock: Box<dyn Clock>) -> Self {
+        Rtc {
+            current: RtcRegisters::default(),
+            latched: RtcRegisters::default(),
+            last_update: clock.now(),
+            clock,
+        }
+    }
+
+    fn update(&mut self) {
+        let now = self.clock.now();
+
+        if !self.current.halt {
+            self.current.advance(now.saturating_sub(self.last_update));
+        }
+
+        self.last_update = now;
+    }
+
+    pub fn latch(&mut self) {
+        self.update();
+        self.latched = self.current;
+    }
+
+    /// Reads return the latched copy, `reg` is the 0x08-0x0C select value.
+    pub fn read(&self, reg: u8) -> u8 {
+        self.latched.read(reg)
+    }
+
+    pub fn write(&mut self, reg: u8, value: u8) {
+        self.update();
+        self.current.write(reg, value);
+    }
+
+    pub fn save(&mut self) -> [u8; RTC_FOOTER_SIZE] {
+        self.update();
+
+        let mut footer = [0; RTC_FOOTER_SIZE];
+        let values = self
+            .current
+            .to_footer()
+            .into_iter()
+            .chain(self.latched.to_footer());
+        for (chunk, value) in footer.chunks_exact_mut(4).zip(values) {
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+        footer[40..].copy_from_slice(&self.last_update.to_le_bytes());
+
+        footer
+    }
+
+    /// Restores a 48 or 44-byte footer, returns false if `footer` is neither.
+    /// Time that passed since the save was written is applied on next access.
+    pub fn load(&mut self, footer: &[u8]) -> bool {
+        if footer.len() != RTC_FOOTER_SIZE && footer.len() != RTC_FOOTER_SIZE_32 {
+            return false;
+        }
+
+        let mut values = footer[..40]
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()));
+        let mut next_five = || std::array::from_fn(|_| values.next().unwrap());
+        self.current = RtcRegisters::from_footer(next_five());
+        self.latched = RtcRegisters::from_footer(next_five());
+
+        let mut timestamp = [0; 8];
+        timestamp[..footer.len() - 40].copy_from_slice(&footer[40..]);
+        self.last_update = u64::from_le_bytes(timestamp);
+
+        true
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::{cell::Cell, rc::Rc};
+
+    /// A clock that only moves when told to.
+    #[derive(Clone)]
+    pub struct FakeClock(pub Rc<Cell<u64>>);
+
+    impl FakeClock {
+        pub fn new(now: u64) -> Self {
+            FakeClock(Rc::new(Cell::new(now)))
+        }
+
+        pub fn advance(&self, seconds: u64) {
+            self.0.set(self.0.get() + seconds);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> u64 {
+            self.0.get()
+        }
+    }
+
+    #[test]
+    fn test_latch_freezes_reads() {
+        let clock = FakeClock::new(1000);
+        let mut rtc = Rtc::new(Box::new(clock.clone()));
+
+        clock.advance(3 * 3600 + 2 * 60 + 1);
+        rtc.latch();
+        clock.advance(10);
+
+        assert_eq!(rtc.read(0x08), 1);
+        assert_eq!(rtc.read(0x09), 2);
+        assert_eq!(rtc.read(0x0A), 3);
+        assert_eq!(rtc.read(0x0B), 0);
+    }
+
+    #[test]
+    fn test_day_counter_overflow_sets_carry() {
+        let clock = FakeClock::new(0);
+        let mut rtc = Rtc::new(Box::new(clock.clone()));
+        rtc.write(0x0B, 0xFF);
+        rtc.write(0x0C, 0x01);
+
+        clock.advance(86400);
+        rtc.latch();
+
+        assert_eq!(rtc.read(0x0B), 0);
+        assert_eq!(rtc.read(0x0C), 0x80);
+    }
+
+    #[test]
+    fn test_halt_stops_the_clock() {
+        let clock = FakeClock::new(0);
+        let mut rtc = Rtc::new(Box::new(clock.clone()));
+        rtc.write(0x0C, 0x40);
+
+        clock.advance(100);
+        rtc.latch();
+        assert_eq!(rtc.read(0x08), 0);
+
+        rtc.write(0x0C, 0x00);
+        clock.advance(5);
+        rtc.latch();
+        assert_eq!(rtc.read(0x08), 5);
+    }
+
+    #[test]
+    fn test_footer_round_trip_applies_elapsed_time() {
+        let clock = FakeClock::new(5000);
+        let mut rtc = Rtc::new(Box::new(clock.clone()));
+        rtc.write(0x09, 10);
+        rtc.latch();
+
+        let footer = rtc.save();
+        assert_eq!(&footer[4..8], &10u32.to_le_bytes());
+        assert_eq!(&footer[24..28], &10u32.to_le_bytes());
+        assert_eq!(&footer[40..], &5000u64.to_le_bytes());
+
+        let mut restored = Rtc::new(Box::new(clock.clone()));
+        assert!(restored.load(&footer));
+        assert_eq!(restored.read(0x09), 10);
+
+        clock.advance(60);
+        restored.latch();
+        assert_eq!(restored.read(0x09), 11);
+    }
+
+    #[test]
+    fn test_load_rejects_bad_footer_size() {
+        let mut rtc = Rtc::new(Box::new(FakeClock::new(0)));
+
+        assert!(!rtc.load(&[0; 12]));
+    }
+}
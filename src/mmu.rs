@@ -1,28 +1,447 @@
-const MEMORY_SIZE: usize = 0x10000; //65356 bytes
+use crate::{
+    bus::Bus,
+    interrupts::{IE_ADDR, IF_ADDR, Interrupt},
+    mbc::{self, Mbc, rtc::Clock},
+    ppu::{LCDC_ADDR, OAM_SIZE, Ppu, WX_ADDR},
+    rom::{Cartridge, CartridgeError},
+    timer::{DIV_ADDR, TAC_ADDR, Timer},
+};
+
+const WRAM_SIZE: usize = 0x2000;
+const IO_SIZE: usize = 0x80;
+const HRAM_SIZE: usize = 0x7F;
+
+const P1_ADDR: u16 = 0xFF00;
+const SB_ADDR: u16 = 0xFF01;
+const SC_ADDR: u16 = 0xFF02;
+const DMA_ADDR: u16 = 0xFF46;
+// CGB speed switch, armed by the CPU and performed by STOP
+const KEY1_ADDR: u16 = 0xFF4D;
 
 pub struct Mmu {
-    pub memory: [u8; MEMORY_SIZE],
+    pub mbc: Option<Box<dyn Mbc>>,
+    // Set when a write changes cartridge RAM, cleared once it has been saved
+    pub cartridge_ram_dirty: bool,
+    wram: [u8; WRAM_SIZE],
+    io: [u8; IO_SIZE],
+    hram: [u8; HRAM_SIZE],
+    pub timer: Timer,
+    pub ppu: Ppu,
+    pub ie: u8,
+    pub int_flag: u8,
+    // Bytes shifted out over the link port, there is never a partner attached
+    pub serial_output: Vec<u8>,
+    // CGB-only registers such as KEY1 exist, the DMG leaves them unmapped
+    pub cgb_mode: bool,
+    // Advance the hardware on each CPU access instead of once per step
+    pub cycle_accurate: bool,
+    // T-cycles already advanced by accesses during the current step
+    ticked_cycles: u8,
 }
 
 impl Mmu {
     pub fn new() -> Self {
         Mmu {
-            memory: [0; MEMORY_SIZE],
+            mbc: None,
+            cartridge_ram_dirty: false,
+            wram: [0; WRAM_SIZE],
+            io: [0; IO_SIZE],
+            hram: [0; HRAM_SIZE],
+            timer: Timer::new(),
+            ppu: Ppu::new(),
+            ie: 0,
+            int_flag: 0,
+            serial_output: Vec::new(),
+            cgb_mode: false,
+            cycle_accurate: false,
+            ticked_cycles: 0,
+        }
+    }
+
+    pub fn load_cartridge(&mut self, cartridge: Cartridge) -> Result<(), CartridgeError> {
+        self.mbc = Some(mbc::from_cartridge(cartridge)?);
+
+        Ok(())
+    }
+
+    /// Like `load_cartridge`, with the time source read by a cartridge RTC.
+    pub fn load_cartridge_with_clock(
+        &mut self,
+        cartridge: Cartridge,
+        clock: Box<dyn Clock>,
+    ) -> Result<(), CartridgeError> {
+        self.mbc = Some(mbc::from_cartridge_with_clock(cartridge, clock)?);
+
+        Ok(())
+    }
+
+    /// Reads `addr` without advancing the hardware. The CPU goes through
+    /// `Bus::read_byte` instead, which ticks first.
+    pub fn peek(&self, addr: u16) -> u8 {
+        match addr {
+            // With no cartridge inserted the data bus floats high
+            0x0000..=0x7FFF => self.mbc.as_ref().map_or(0xFF, |mbc| mbc.read_rom(addr)),
+            0x8000..=0x9FFF => self.ppu.read_vram(addr),
+            0xA000..=0xBFFF => self.mbc.as_ref().map_or(0xFF, |mbc| mbc.read_ram(addr)),
+            0xC000..=0xDFFF => self.wram[(addr - 0xC000) as usize],
+            // Echo RAM mirrors 0xC000-0xDDFF
+            0xE000..=0xFDFF => self.wram[(addr - 0xE000) as usize],
+            0xFE00..=0xFE9F => self.ppu.read_oam(addr),
+            // The unusable area reads 0x00 on DMG while OAM is accessible
+            0xFEA0..=0xFEFF => 0x00,
+            0xFF00..=0xFF7F => self.read_io(addr),
+            0xFF80..=0xFFFE => self.hram[(addr - 0xFF80) as usize],
+            IE_ADDR => self.ie,
+        }
+    }
+
+    /// Writes `addr` without advancing the hardware, see `peek`.
+    pub fn poke(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x7FFF => {
+                if let Some(mbc) = &mut self.mbc {
+                    mbc.write_rom(addr, value);
+                }
+            }
+            0x8000..=0x9FFF => self.ppu.write_vram(addr, value),
+            0xA000..=0xBFFF => {
+                if let Some(mbc) = &mut self.mbc {
+                    self.cartridge_ram_dirty |= mbc.write_ram(addr, value);
+                }
+            }
+            0xC000..=0xDFFF => self.wram[(addr - 0xC000) as usize] = value,
+            0xE000..=0xFDFF => self.wram[(addr - 0xE000) as usize] = value,
+            0xFE00..=0xFE9F => self.ppu.write_oam(addr, value),
+            0xFEA0..=0xFEFF => {}
+            0xFF00..=0xFF7F => self.write_io(addr, value),
+            0xFF80..=0xFFFE => self.hram[(addr - 0xFF80) as usize] = value,
+            IE_ADDR => self.ie = value,
         }
     }
 
-    pub fn read_byte(&self, addr: u16) -> u8 {
-        self.memory[addr as usize]
+    fn read_io(&self, addr: u16) -> u8 {
+        match addr {
+            // No buttons are wired up yet, so every input line reads released
+            P1_ADDR => 0xC0 | (self.io[0x00] & 0x30) | 0x0F,
+            DIV_ADDR..=TAC_ADDR => self.timer.read(addr),
+            // Only the lower 5 bits of IF are wired, the rest read as 1
+            IF_ADDR => self.int_flag | 0xE0,
+            DMA_ADDR => self.io[(addr - 0xFF00) as usize],
+            LCDC_ADDR..=WX_ADDR => self.ppu.read(addr),
+            // Only the armed bit and the current speed in bit 7
+            KEY1_ADDR if self.cgb_mode => self.io[(addr - 0xFF00) as usize] | 0x7E,
+            _ => self.io[(addr - 0xFF00) as usize] | io_unused_bits(addr),
+        }
+    }
+
+    fn write_io(&mut self, addr: u16, value: u8) {
+        let index = (addr - 0xFF00) as usize;
+
+        match addr {
+            P1_ADDR => self.io[index] = value & 0x30,
+            DIV_ADDR..=TAC_ADDR => self.timer.write(addr, value),
+            SC_ADDR => {
+                self.io[index] = value;
+                if value & 0x81 == 0x81 {
+                    self.serial_transfer();
+                }
+            }
+            IF_ADDR => self.int_flag = value & 0x1F,
+            DMA_ADDR => {
+                self.io[index] = value;
+                self.oam_dma(value);
+            }
+            LCDC_ADDR..=WX_ADDR => self.int_flag |= self.ppu.write(addr, value),
+            // The current speed is read-only, only STOP changes it
+            KEY1_ADDR if self.cgb_mode => self.io[index] = self.io[index] & 0x80 | value & 0x01,
+            KEY1_ADDR => {}
+            _ => self.io[index] = value,
+        }
+    }
+
+    /// Completes an internally clocked transfer at once. With nothing on the
+    /// other end of the cable the incoming bits are all 1.
+    fn serial_transfer(&mut self) {
+        let sb = (SB_ADDR - 0xFF00) as usize;
+        let sc = (SC_ADDR - 0xFF00) as usize;
+
+        self.serial_output.push(self.io[sb]);
+        self.io[sb] = 0xFF;
+        self.io[sc] &= 0x7F;
+        self.request_interrupt(Interrupt::Serial);
+    }
+
+    /// Copies 0xA0 bytes from `page` << 8 into OAM. The transfer is performed
+    /// at once rather than over the 160 M-cycles it takes on hardware.
+    fn oam_dma(&mut self, page: u8) {
+        // Sources above 0xDFFF are decoded as echo RAM by the DMA unit
+        let source = match (page as u16) << 8 {
+            base @ 0xE000..=0xFFFF => base - 0x2000,
+            base => base,
+        };
+
+        for i in 0..OAM_SIZE as u16 {
+            self.ppu.oam[i as usize] = self.read(source + i);
+        }
+    }
+
+    /// Advances the hardware clocked alongside the CPU by `cycles` T-cycles.
+    pub fn advance(&mut self, cycles: u8) {
+        if self.timer.tick(cycles) {
+            self.request_interrupt(Interrupt::Timer);
+        }
+        self.int_flag |= self.ppu.tick(cycles);
+    }
+
+    /// Returns the T-cycles advanced by CPU accesses since the last call.
+    pub fn take_ticked_cycles(&mut self) -> u8 {
+        std::mem::take(&mut self.ticked_cycles)
+    }
+
+    pub fn request_interrupt(&mut self, interrupt: Interrupt) {
+        self.int_flag |= interrupt.bit();
+    }
+}
+
+impl Default for Mmu {
+    fn default() -> Self {
+        Mmu::new()
+    }
+}
+
+impl Bus for Mmu {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.peek(addr)
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        self.poke(addr, value);
+    }
+
+    fn switch_speed(&mut self) -> bool {
+        let key1 = (KEY1_ADDR - 0xFF00) as usize;
+        if !self.cgb_mode || self.io[key1] & 0x01 == 0 {
+            return false;
+        }
+
+        self.io[key1] = (self.io[key1] ^ 0x80) & 0x80;
+        true
+    }
+
+    fn tick(&mut self) {
+        if self.cycle_accurate {
+            self.advance(4);
+            self.ticked_cycles += 4;
+        }
+    }
+}
+
+/// Bits of a DMG I/O register that are not wired and always read as 1.
+/// Unmapped registers read as 0xFF.
+fn io_unused_bits(addr: u16) -> u8 {
+    match addr {
+        0xFF01 => 0x00,
+        0xFF02 => 0x7E,
+        // Sound registers, write-only bits also read back as 1
+        0xFF10 => 0x80,
+        0xFF11 | 0xFF16 => 0x3F,
+        0xFF12 | 0xFF17 | 0xFF21 | 0xFF22 | 0xFF24 | 0xFF25 => 0x00,
+        0xFF14 | 0xFF19 | 0xFF1E | 0xFF23 => 0xBF,
+        0xFF1A => 0x7F,
+        0xFF1C => 0x9F,
+        0xFF26 => 0x70,
+        0xFF30..=0xFF3F => 0x00,
+        _ => 0xFF,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mmu;
+    use crate::{
+        bus::Bus,
+        interrupts::Interrupt,
+        rom::{Cartridge, tests::build_rom},
+    };
+
+    #[test]
+    fn test_cartridge_routing() {
+        let mut rom = build_rom(0x03, 0x02, 0x02);
+        rom[0x4000 * 3] = 0x33;
+        let cartridge = Cartridge::from_bytes(rom).unwrap();
+        let mut mmu = Mmu::new();
+        mmu.load_cartridge(cartridge).unwrap();
+
+        mmu.poke(0x2000, 0x03);
+        assert_eq!(mmu.peek(0x4000), 0x33);
+
+        mmu.poke(0x0000, 0x0A);
+        mmu.poke(0xA123, 0x42);
+        assert_eq!(mmu.peek(0xA123), 0x42);
+    }
+
+    #[test]
+    fn test_cartridge_ram_dirty_only_when_stored() {
+        let cartridge = Cartridge::from_bytes(build_rom(0x03, 0x02, 0x02)).unwrap();
+        let mut mmu = Mmu::new();
+        mmu.load_cartridge(cartridge).unwrap();
+
+        // RAM is disabled until 0x0A is written to 0x0000-0x1FFF
+        mmu.poke(0xA000, 0x42);
+        assert!(!mmu.cartridge_ram_dirty);
+
+        mmu.poke(0x0000, 0x0A);
+        mmu.poke(0xA000, 0x42);
+        assert!(mmu.cartridge_ram_dirty);
     }
 
-    pub fn read_word(&self, addr: u16) -> u16 {
-        let low = self.memory[addr as usize] as u16;
-        let high = self.memory[(addr + 1) as usize] as u16;
+    #[test]
+    fn test_rom_is_read_only_without_cartridge() {
+        let mut mmu = Mmu::new();
+
+        mmu.poke(0x0100, 0x42);
+        mmu.poke(0xA000, 0x42);
 
-        (high << 8) | low
+        assert_eq!(mmu.peek(0x0100), 0xFF);
+        assert_eq!(mmu.peek(0xA000), 0xFF);
+        assert!(!mmu.cartridge_ram_dirty);
     }
 
-    pub fn write_byte(&mut self, addr: u16, value: u8) {
-        self.memory[addr as usize] = value;
+    #[test]
+    fn test_echo_ram_mirrors_wram() {
+        let mut mmu = Mmu::new();
+
+        mmu.poke(0xC123, 0x11);
+        mmu.poke(0xFDFF, 0x22);
+
+        assert_eq!(mmu.peek(0xE123), 0x11);
+        assert_eq!(mmu.peek(0xDDFF), 0x22);
+    }
+
+    #[test]
+    fn test_unusable_area_ignores_writes() {
+        let mut mmu = Mmu::new();
+
+        mmu.poke(0xFEA0, 0x42);
+        mmu.poke(0xFEFF, 0x42);
+
+        assert_eq!(mmu.peek(0xFEA0), 0x00);
+        assert_eq!(mmu.peek(0xFEFF), 0x00);
+    }
+
+    #[test]
+    fn test_io_unused_bits_read_as_one() {
+        let mut mmu = Mmu::new();
+
+        mmu.poke(0xFF07, 0x00);
+        mmu.poke(0xFF03, 0x00);
+        mmu.poke(0xFF00, 0x20);
+
+        assert_eq!(mmu.peek(0xFF07), 0xF8);
+        assert_eq!(mmu.peek(0xFF03), 0xFF);
+        assert_eq!(mmu.peek(0xFF00), 0xEF);
+    }
+
+    #[test]
+    fn test_io_write_side_effects() {
+        let mut mmu = Mmu::new();
+
+        mmu.poke(0xFF04, 0xAB);
+        mmu.poke(0xFF44, 0x12);
+        mmu.poke(0xFF41, 0xFF);
+
+        assert_eq!(mmu.peek(0xFF04), 0x00);
+        assert_eq!(mmu.peek(0xFF44), 0x00);
+        assert_eq!(mmu.peek(0xFF41), 0xF8);
+    }
+
+    #[test]
+    fn test_oam_dma_copies_page() {
+        let mut mmu = Mmu::new();
+        mmu.poke(0xC000, 0x11);
+        mmu.poke(0xC09F, 0x22);
+
+        mmu.poke(0xFF46, 0xC0);
+
+        assert_eq!(mmu.peek(0xFE00), 0x11);
+        assert_eq!(mmu.peek(0xFE9F), 0x22);
+    }
+
+    #[test]
+    fn test_hram_and_ie() {
+        let mut mmu = Mmu::new();
+
+        mmu.poke(0xFF80, 0x12);
+        mmu.poke(0xFFFE, 0x34);
+        mmu.poke(0xFFFF, 0x1F);
+
+        assert_eq!(mmu.peek(0xFF80), 0x12);
+        assert_eq!(mmu.peek(0xFFFE), 0x34);
+        assert_eq!(mmu.ie, 0x1F);
+    }
+
+    #[test]
+    fn test_word_access_wraps_around() {
+        let mut mmu = Mmu::new();
+
+        mmu.write_word(0xFFFF, 0x1234);
+
+        assert_eq!(mmu.ie, 0x34);
+        assert_eq!(mmu.read_word(0xFFFF), 0xFF34);
+    }
+
+    #[test]
+    fn test_timer_overflow_requests_interrupt() {
+        let mut mmu = Mmu::new();
+        mmu.poke(0xFF07, 0x05);
+        mmu.poke(0xFF05, 0xFF);
+
+        mmu.advance(16);
+        assert_eq!(mmu.int_flag, 0);
+
+        mmu.advance(4);
+        assert_eq!(mmu.int_flag, Interrupt::Timer.bit());
+    }
+
+    #[test]
+    fn test_stat_interrupt_requested() {
+        let mut mmu = Mmu::new();
+        mmu.poke(0xFF40, 0x80);
+        mmu.poke(0xFF41, 0x08);
+
+        mmu.advance(80 + 172);
+
+        assert_eq!(mmu.int_flag, Interrupt::LcdStat.bit());
+        assert_eq!(mmu.peek(0xFF0F), 0xE2);
+    }
+
+    #[test]
+    fn test_cycle_accurate_access_advances_timer() {
+        let mut mmu = Mmu::new();
+        mmu.poke(0xFF07, 0x05);
+
+        mmu.read_byte(0xC000);
+        assert_eq!(mmu.take_ticked_cycles(), 0);
+
+        mmu.cycle_accurate = true;
+        for _ in 0..4 {
+            mmu.read_byte(0xC000);
+        }
+
+        assert_eq!(mmu.take_ticked_cycles(), 16);
+        assert_eq!(mmu.peek(0xFF05), 1);
+    }
+
+    #[test]
+    fn test_serial_transfer_completes_immediately() {
+        let mut mmu = Mmu::new();
+
+        mmu.poke(0xFF01, b'P');
+        mmu.poke(0xFF02, 0x81);
+
+        assert_eq!(mmu.serial_output, b"P");
+        assert_eq!(mmu.peek(0xFF01), 0xFF);
+        assert_eq!(mmu.peek(0xFF02), 0x7F);
+        assert_eq!(mmu.int_flag, Interrupt::Serial.bit());
     }
 }
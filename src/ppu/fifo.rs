@@ -0,0 +1,277 @@
+//! Dot-by-dot renderer modelling the DMG pixel fetcher and the BG and OBJ
+//! FIFOs. Registers are sampled as the fetcher and the LCD get to them, so
+//! mid-line writes take effect on the next pixel, and mode 3 stretches with
+//! SCX fine scroll, the window and sprite fetches the way it does on hardware.
+
+use std::collections::VecDeque;
+
+use crate::ppu::{LCDC_BG_ENABLE, LCDC_OBJ_ENABLE, Ppu, SCREEN_WIDTH, sprite::Sprite, tile};
+
+// The first tile fetch of every line is thrown away
+const STARTUP_DOTS: u8 = 6;
+// Tile number, low data and high data steps of a sprite fetch
+const SPRITE_FETCH_DOTS: u8 = 6;
+const FETCH_STEP_DOTS: u8 = 2;
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+enum FetchStep {
+    #[default]
+    Tile,
+    DataLow,
+    DataHigh,
+    // Waits until the BG FIFO is empty before pushing 8 pixels
+    Push,
+}
+
+#[derive(Default)]
+struct Fetcher {
+    step: FetchStep,
+    dots: u8,
+    // Tile column relative to the start of the BG or window row
+    tile_x: u8,
+    tile: u8,
+    low: u8,
+    high: u8,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ObjPixel {
+    color: u8,
+    obp1: bool,
+    behind_bg: bool,
+}
+
+#[derive(Default)]
+pub struct FifoState {
+    bg: VecDeque<u8>,
+    obj: VecDeque<ObjPixel>,
+    fetcher: Fetcher,
+    startup: u8,
+    // Pixels sent to the LCD so far on this line
+    lx: u8,
+    // Pixels still to be dropped, from SCX fine scroll or WX below 7
+    discard: u8,
+    window_active: bool,
+    // Sprites from the OAM scan not fetched yet, by X then OAM order
+    pending_sprites: VecDeque<Sprite>,
+    // Dots left of the sprite fetch stalling the line, and the sprite
+    sprite_fetch: Option<(u8, Sprite)>,
+    // Tile column, counted from the first fetched tile, of the last sprite
+    sprite_tile: Option<u8>,
+}
+
+impl Ppu {
+    pub(super) fn start_fifo_line(&mut self) {
+        let mut sprites = self.line_sprites.clone();
+        sprites.sort_by_key(|sprite| (sprite.x, sprite.index));
+
+        self.fifo = FifoState {
+            startup: STARTUP_DOTS,
+            discard: self.scx % 8,
+            pending_sprites: sprites.into(),
+            ..FifoState::default()
+        };
+
+        // WX=166 on the previous line leaves the window on from the start
+        if std::mem::take(&mut self.window_wrap) && self.window_enabled() {
+            self.start_window(0);
+        }
+    }
+
+    /// Runs one dot of mode 3. Returns true once the line is complete.
+    pub(super) fn fifo_dot(&mut self) -> bool {
+        if self.fifo.startup > 0 {
+            self.fifo.startup -= 1;
+            return false;
+        }
+
+        if self.fifo.sprite_fetch.is_none() && self.sprite_due() {
+            let sprite = self.fifo.pending_sprites.pop_front().unwrap();
+            let dots = self.bg_fetch_wait() + SPRITE_FETCH_DOTS;
+            self.fifo.sprite_fetch = Some((dots, sprite));
+        }
+
+        if let Some((dots, sprite)) = self.fifo.sprite_fetch {
+            if dots > 1 {
+                self.fifo.sprite_fetch = Some((dots - 1, sprite));
+            } else {
+                self.fifo.sprite_fetch = None;
+                self.merge_sprite(sprite);
+            }
+            return false;
+        }
+
+        self.check_window_start();
+        self.step_fetcher();
+        self.shift_pixel()
+    }
+
+    // Whether the next sprite starts at the current pixel. Sprites are
+    // skipped without a fetch while objects are disabled.
+    fn sprite_due(&mut self) -> bool {
+        while let Some(sprite) = self.fifo.pending_sprites.front() {
+            if sprite.x > self.fifo.lx + 8 {
+                return false;
+            }
+            if self.lcdc & LCDC_OBJ_ENABLE != 0 {
+                return true;
+            }
+            self.fifo.pending_sprites.pop_front();
+        }
+
+        false
+    }
+
+    // Dots spent letting the BG fetch under way finish before a sprite fetch,
+    // up to 5 at the start of a tile and none from its 6th pixel on. Later
+    // sprites on the same tile find the fetch already done.
+    fn bg_fetch_wait(&mut self) -> u8 {
+        let offset = self.fifo.lx + self.scx % 8;
+        let tile = offset / 8;
+
+        if self.fifo.sprite_tile.replace(tile) == Some(tile) {
+            return 0;
+        }
+        5 - (offset % 8).min(5)
+    }
+
+    fn step_fetcher(&mut self) {
+        let fetcher = &mut self.fifo.fetcher;
+
+        if fetcher.step == FetchStep::Push {
+            if self.fifo.bg.is_empty() {
+                let (low, high) = (fetcher.low, fetcher.high);
+                self.fifo.bg.extend(
+                    (0..8)
+                        .rev()
+                        .map(|bit| ((high >> bit) & 1) << 1 | (low >> bit) & 1),
+                );
+                self.fifo.fetcher.step = FetchStep::Tile;
+                self.fifo.fetcher.tile_x = self.fifo.fetcher.tile_x.wrapping_add(1);
+            }
+            return;
+        }
+
+        fetcher.dots += 1;
+        if fetcher.dots < FETCH_STEP_DOTS {
+            return;
+        }
+        fetcher.dots = 0;
+
+        let (map_base, x, y) = if self.fifo.window_active {
+            (
+                self.window_map_base(),
+                self.fifo.fetcher.tile_x * 8,
+                self.window_line,
+            )
+        } else {
+            let x = (self.scx & 0xF8).wrapping_add(self.fifo.fetcher.tile_x * 8);
+            (self.bg_map_base(), x, self.ly.wrapping_add(self.scy))
+        };
+
+        let row_addr = self.tile_addr(self.fifo.fetcher.tile) + (y % 8) as usize * 2;
+        let fetcher = &mut self.fifo.fetcher;
+        match fetcher.step {
+            FetchStep::Tile => {
+                fetcher.tile = tile::tile_at(&self.vram, map_base, x, y);
+                fetcher.step = FetchStep::DataLow;
+            }
+            FetchStep::DataLow => {
+                fetcher.low = self.vram[row_addr];
+                fetcher.step = FetchStep::DataHigh;
+            }
+            FetchStep::DataHigh => {
+                fetcher.high = self.vram[row_addr + 1];
+                fetcher.step = FetchStep::Push;
+            }
+            FetchStep::Push => unreachable!(),
+        }
+    }
+
+    // The window starts once the LCD reaches WX - 7, throwing away the BG
+    // pixels already fetched. WX=166 is too late for this line and instead
+    // covers the whole of the next one.
+    fn check_window_start(&mut self) {
+        if self.fifo.window_active || !self.window_enabled() {
+            return;
+        }
+
+        match self.wx {
+            166 => self.window_wrap = true,
+            wx if wx < 166 && self.fifo.lx + 7 >= wx => {
+                self.start_window(7u8.saturating_sub(wx));
+            }
+            _ => {}
+        }
+    }
+
+    fn start_window(&mut self, discard: u8) {
+        self.fifo.window_active = true;
+        self.fifo.discard = discard;
+        self.fifo.bg.clear();
+        self.fifo.fetcher = Fetcher::default();
+    }
+
+    fn merge_sprite(&mut self, sprite: Sprite) {
+        // Columns already left of the LCD are cut off
+        let clipped = (self.fifo.lx + 8).saturating_sub(sprite.x);
+
+        for column in clipped..8 {
+            let pixel = ObjPixel {
+                color: sprite.column_pixel(&self.vram, column, self.ly),
+                obp1: sprite.uses_obp1(),
+                behind_bg: sprite.behind_bg(),
+            };
+
+            // Sprites are fetched in priority order, so only transparent
+            // pixels of earlier sprites can be replaced
+            match self.fifo.obj.get_mut((column - clipped) as usize) {
+                Some(existing) if existing.color == 0 => *existing = pixel,
+                Some(_) => {}
+                None => self.fifo.obj.push_back(pixel),
+            }
+        }
+    }
+
+    fn shift_pixel(&mut self) -> bool {
+        let Some(bg_color) = self.fifo.bg.pop_front() else {
+            return false;
+        };
+
+        if self.fifo.discard > 0 {
+            self.fifo.discard -= 1;
+            return false;
+        }
+
+        // On DMG a disabled background is drawn as white, not color 0
+        let bg_color = if self.lcdc & LCDC_BG_ENABLE != 0 {
+            bg_color
+        } else {
+            0
+        };
+        let mut shade = tile::shade(self.bgp, bg_color);
+
+        if let Some(obj) = self.fifo.obj.pop_front()
+            && obj.color != 0
+            && self.lcdc & LCDC_OBJ_ENABLE != 0
+            && !(obj.behind_bg && bg_color != 0)
+        {
+            let palette = if obj.obp1 { self.obp1 } else { self.obp0 };
+            shade = tile::shade(palette, obj.color);
+        }
+
+        let x = self.fifo.lx as usize;
+        self.back_buffer[self.ly as usize * SCREEN_WIDTH + x] = shade;
+        self.fifo.lx += 1;
+
+        if x + 1 < SCREEN_WIDTH {
+            return false;
+        }
+
+        if self.fifo.window_active {
+            self.window_line += 1;
+        }
+
+        true
+    }
+}
@@ -0,0 +1,1035 @@
+mod fifo;
+mod scanline;
+mod sprite;
+mod tile;
+
+use crate::{
+    interrupts::Interrupt,
+    ppu::{fifo::FifoState, sprite::Sprite},
+};
+
+pub const SCREEN_WIDTH: usize = 160;
+pub const SCREEN_HEIGHT: usize = 144;
+pub const FRAME_SIZE: usize = SCREEN_WIDTH * SCREEN_HEIGHT;
+
+pub const VRAM_SIZE: usize = 0x2000;
+pub const OAM_SIZE: usize = 0xA0;
+
+pub const LCDC_ADDR: u16 = 0xFF40;
+pub const STAT_ADDR: u16 = 0xFF41;
+pub const SCY_ADDR: u16 = 0xFF42;
+pub const SCX_ADDR: u16 = 0xFF43;
+pub const LY_ADDR: u16 = 0xFF44;
+pub const LYC_ADDR: u16 = 0xFF45;
+pub const BGP_ADDR: u16 = 0xFF47;
+pub const OBP0_ADDR: u16 = 0xFF48;
+pub const OBP1_ADDR: u16 = 0xFF49;
+pub const WY_ADDR: u16 = 0xFF4A;
+pub const WX_ADDR: u16 = 0xFF4B;
+
+const LCDC_ENABLE: u8 = 0x80;
+const LCDC_WINDOW_TILE_MAP: u8 = 0x40;
+const LCDC_WINDOW_ENABLE: u8 = 0x20;
+const LCDC_BG_TILE_MAP: u8 = 0x08;
+const LCDC_TILE_DATA: u8 = 0x10;
+const LCDC_OBJ_SIZE: u8 = 0x04;
+const LCDC_OBJ_ENABLE: u8 = 0x02;
+const LCDC_BG_ENABLE: u8 = 0x01;
+
+const STAT_LYC_SELECT: u8 = 0x40;
+const STAT_OAM_SELECT: u8 = 0x20;
+const STAT_VBLANK_SELECT: u8 = 0x10;
+const STAT_HBLANK_SELECT: u8 = 0x08;
+const STAT_SELECT_MASK: u8 = 0x78;
+
+const LINE_DOTS: u16 = 456;
+const OAM_SCAN_DOTS: u16 = 80;
+// Mode 3 without any sprite, window or SCX penalties
+const DRAWING_DOTS: u16 = 172;
+const LINES_PER_FRAME: u8 = 154;
+
+/// The two low bits of STAT, in the order the hardware numbers them.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Mode {
+    HBlank = 0,
+    VBlank = 1,
+    OamScan = 2,
+    Drawing = 3,
+}
+
+/// How mode 3 turns VRAM into pixels.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Renderer {
+    /// Draws the whole line at once with a fixed-length mode 3. Cheap, but
+    /// register writes during mode 3 only show up on the next line.
+    Scanline,
+    /// Emulates the pixel fetcher and FIFOs dot by dot, with a variable-length
+    /// mode 3 and mid-line register changes visible where they happen.
+    PixelFifo,
+}
+
+pub struct Ppu {
+    renderer: Renderer,
+    pub vram: [u8; VRAM_SIZE],
+    pub oam: [u8; OAM_SIZE],
+    lcdc: u8,
+    // Only the interrupt select bits 3-6 are stored, the rest is derived
+    stat: u8,
+    scy: u8,
+    scx: u8,
+    ly: u8,
+    lyc: u8,
+    bgp: u8,
+    obp0: u8,
+    obp1: u8,
+    wy: u8,
+    wx: u8,
+    mode: Mode,
+    // Position within the current line, 0-455
+    dot: u16,
+    lyc_equal: bool,
+    // The selected STAT sources OR'd together, the interrupt is only raised
+    // when this goes from low to high
+    stat_line: bool,
+    // Set once LY has matched WY this frame, the window can only show after
+    wy_triggered: bool,
+    // Window row to draw next, only advances on lines that showed the window
+    window_line: u8,
+    // WX=166 on the previous line, the window covers all of this one
+    window_wrap: bool,
+    // Sprites found by the OAM scan for the current line
+    line_sprites: Vec<Sprite>,
+    // Lines are drawn into the back buffer, which is swapped to the front
+    // at the start of VBlank so readers always see a whole frame
+    back_buffer: Box<[u8; FRAME_SIZE]>,
+    front_buffer: Box<[u8; FRAME_SIZE]>,
+    fifo: FifoState,
+}
+
+impl Ppu {
+    pub fn new() -> Self {
+        Ppu::with_renderer(Renderer::Scanline)
+    }
+
+    pub fn with_renderer(renderer: Renderer) -> Self {
+        Ppu {
+            renderer,
+            vram: [0; VRAM_SIZE],
+            oam: [0; OAM_SIZE],
+            lcdc: 0,
+            stat: 0,
+            scy: 0,
+            scx: 0,
+            ly: 0,
+            lyc: 0,
+            bgp: 0,
+            obp0: 0,
+            obp1: 0,
+            wy: 0,
+            wx: 0,
+            mode: Mode::HBlank,
+            dot: 0,
+            lyc_equal: false,
+            stat_line: false,
+            wy_triggered: false,
+            window_line: 0,
+            window_wrap: false,
+            line_sprites: Vec::new(),
+            back_buffer: Box::new([0; FRAME_SIZE]),
+            front_buffer: Box::new([0; FRAME_SIZE]),
+            fifo: FifoState::default(),
+        }
+    }
+
+    /// The last complete frame, one shade (0 = white to 3 = black) per pixel
+    /// in row-major order.
+    pub fn frame(&self) -> &[u8; FRAME_SIZE] {
+        &self.front_buffer
+    }
+
+    fn lcd_enabled(&self) -> bool {
+        self.lcdc & LCDC_ENABLE != 0
+    }
+
+    /// Advances the PPU by `cycles` dots (T-cycles). Returns the IF bits of
+    /// the interrupts it raised.
+    pub fn tick(&mut self, cycles: u8) -> u8 {
+        if !self.lcd_enabled() {
+            return 0;
+        }
+
+        let mut interrupts = 0;
+        for _ in 0..cycles {
+            interrupts |= self.tick_dot();
+            interrupts |= self.update_stat_line(self.stat);
+        }
+
+        interrupts
+    }
+
+    fn tick_dot(&mut self) -> u8 {
+        self.dot += 1;
+
+        match self.mode {
+            Mode::OamScan if self.dot == OAM_SCAN_DOTS => {
+                self.line_sprites = sprite::scan_oam(&self.oam, self.ly, self.sprite_height());
+                self.wy_triggered |= self.ly == self.wy;
+                self.mode = Mode::Drawing;
+                if self.renderer == Renderer::PixelFifo {
+                    self.start_fifo_line();
+                }
+            }
+            Mode::Drawing => self.draw_dot(),
+            _ if self.dot == LINE_DOTS => return self.next_line(),
+            _ => {}
+        }
+
+        0
+    }
+
+    fn draw_dot(&mut self) {
+        let line_done = match self.renderer {
+            Renderer::Scanline => {
+                let done = self.dot == OAM_SCAN_DOTS + DRAWING_DOTS;
+                if done {
+                    self.render_line();
+                }
+                done
+            }
+            Renderer::PixelFifo => self.fifo_dot(),
+        };
+
+        if line_done {
+            self.mode = Mode::HBlank;
+        }
+    }
+
+    fn next_line(&mut self) -> u8 {
+        self.dot = 0;
+        self.ly = (self.ly + 1) % LINES_PER_FRAME;
+        self.compare_lyc();
+
+        match self.ly as usize {
+            SCREEN_HEIGHT => {
+                self.mode = Mode::VBlank;
+                self.reset_window();
+                std::mem::swap(&mut self.back_buffer, &mut self.front_buffer);
+                Interrupt::VBlank.bit()
+            }
+            0..SCREEN_HEIGHT => {
+                self.mode = Mode::OamScan;
+                0
+            }
+            _ => 0,
+        }
+    }
+
+    // Recomputes the STAT line with the sources in `select` and returns the
+    // STAT interrupt bit on a rising edge. While one source holds the line
+    // high, others becoming true do not raise another interrupt.
+    fn update_stat_line(&mut self, select: u8) -> u8 {
+        let line = self.lcd_enabled()
+            && (select & STAT_LYC_SELECT != 0 && self.lyc_equal
+                || select & STAT_OAM_SELECT != 0 && self.mode == Mode::OamScan
+                || select & STAT_VBLANK_SELECT != 0 && self.mode == Mode::VBlank
+                || select & STAT_HBLANK_SELECT != 0 && self.mode == Mode::HBlank);
+
+        let rising = line && !self.stat_line;
+        self.stat_line = line;
+
+        if rising { Interrupt::LcdStat.bit() } else { 0 }
+    }
+
+    fn reset_window(&mut self) {
+        self.wy_triggered = false;
+        self.window_line = 0;
+        self.window_wrap = false;
+    }
+
+    fn compare_lyc(&mut self) {
+        self.lyc_equal = self.ly == self.lyc;
+    }
+
+    fn sprite_height(&self) -> u8 {
+        if self.lcdc & LCDC_OBJ_SIZE != 0 {
+            16
+        } else {
+            8
+        }
+    }
+
+    fn bg_map_base(&self) -> usize {
+        if self.lcdc & LCDC_BG_TILE_MAP != 0 {
+            0x1C00
+        } else {
+            0x1800
+        }
+    }
+
+    fn window_map_base(&self) -> usize {
+        if self.lcdc & LCDC_WINDOW_TILE_MAP != 0 {
+            0x1C00
+        } else {
+            0x1800
+        }
+    }
+
+    // LCDC allows the window and LY has reached WY at some point this frame
+    fn window_enabled(&self) -> bool {
+        self.lcdc & LCDC_WINDOW_ENABLE != 0 && self.wy_triggered
+    }
+
+    // Offset into VRAM of a BG/window tile. With LCDC bit 4 clear, indices
+    // are signed and relative to 0x9000, so 0x80-0xFF share 0x8800-0x8FFF
+    // with the 0x8000 addressing mode.
+    fn tile_addr(&self, tile: u8) -> usize {
+        if self.lcdc & LCDC_TILE_DATA != 0 {
+            tile as usize * 16
+        } else {
+            (0x1000 + tile as i8 as isize * 16) as usize
+        }
+    }
+
+    /// VRAM as seen by the CPU, which cannot access it while pixels are drawn.
+    pub fn read_vram(&self, addr: u16) -> u8 {
+        match self.mode {
+            Mode::Drawing => 0xFF,
+            _ => self.vram[(addr - 0x8000) as usize],
+        }
+    }
+
+    pub fn write_vram(&mut self, addr: u16, value: u8) {
+        if self.mode != Mode::Drawing {
+            self.vram[(addr - 0x8000) as usize] = value;
+        }
+    }
+
+    /// OAM as seen by the CPU, locked during OAM scan and drawing.
+    pub fn read_oam(&self, addr: u16) -> u8 {
+        match self.mode {
+            Mode::OamScan | Mode::Drawing => 0xFF,
+            _ => self.oam[(addr - 0xFE00) as usize],
+        }
+    }
+
+    pub fn write_oam(&mut self, addr: u16, value: u8) {
+        if !matches!(self.mode, Mode::OamScan | Mode::Drawing) {
+            self.oam[(addr - 0xFE00) as usize] = value;
+        }
+    }
+
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            LCDC_ADDR => self.lcdc,
+            STAT_ADDR => 0x80 | self.stat | (self.lyc_equal as u8) << 2 | self.mode as u8,
+            SCY_ADDR => self.scy,
+            SCX_ADDR => self.scx,
+            LY_ADDR => self.ly,
+            LYC_ADDR => self.lyc,
+            BGP_ADDR => self.bgp,
+            OBP0_ADDR => self.obp0,
+            OBP1_ADDR => self.obp1,
+            WY_ADDR => self.wy,
+            WX_ADDR => self.wx,
+            _ => 0xFF,
+        }
+    }
+
+    /// Returns the IF bits of the interrupts raised by the write.
+    pub fn write(&mut self, addr: u16, value: u8) -> u8 {
+        let mut interrupts = 0;
+
+        match addr {
+            LCDC_ADDR => self.write_lcdc(value),
+            STAT_ADDR => interrupts = self.write_stat(value),
+            SCY_ADDR => self.scy = value,
+            SCX_ADDR => self.scx = value,
+            LY_ADDR => {}
+            LYC_ADDR => {
+                self.lyc = value;
+                if self.lcd_enabled() {
+                    self.compare_lyc();
+                }
+            }
+            BGP_ADDR => self.bgp = value,
+            OBP0_ADDR => self.obp0 = value,
+            OBP1_ADDR => self.obp1 = value,
+            WY_ADDR => self.wy = value,
+            WX_ADDR => self.wx = value,
+            _ => {}
+        }
+
+        // Turning the LCD on or changing LYC or the selects can raise the line
+        interrupts | self.update_stat_line(self.stat)
+    }
+
+    // On DMG the write briefly selects every source before the new value
+    // lands, so a STAT interrupt fires if any of them is true at the time.
+    // Some games rely on this to get an interrupt during HBlank or VBlank.
+    fn write_stat(&mut self, value: u8) -> u8 {
+        let interrupts = self.update_stat_line(STAT_SELECT_MASK);
+        // The mode and coincidence bits are read-only
+        self.stat = value & STAT_SELECT_MASK;
+
+        interrupts
+    }
+
+    fn write_lcdc(&mut self, value: u8) {
+        let was_enabled = self.lcd_enabled();
+        self.lcdc = value;
+
+        match (was_enabled, self.lcd_enabled()) {
+            // Turning the LCD off resets LY and leaves the PPU idle in mode 0
+            (true, false) => {
+                self.ly = 0;
+                self.dot = 0;
+                self.mode = Mode::HBlank;
+            }
+            (false, true) => {
+                self.mode = Mode::OamScan;
+                self.reset_window();
+                self.compare_lyc();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Default for Ppu {
+    fn default() -> Self {
+        Ppu::new()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    const TILE_DATA_ADDR: u16 = 0x8000;
+    pub const RENDERERS: [Renderer; 2] = [Renderer::Scanline, Renderer::PixelFifo];
+
+    // A PPU with the LCD and background on, tile data at 0x8000 and the
+    // identity palette
+    fn enabled_ppu(renderer: Renderer) -> Ppu {
+        let mut ppu = Ppu::with_renderer(renderer);
+        ppu.write(BGP_ADDR, 0xE4);
+        ppu.write(LCDC_ADDR, 0x91);
+
+        ppu
+    }
+
+    // Returns the interrupts raised along the way
+    fn run_lines(ppu: &mut Ppu, lines: u32) -> u8 {
+        let mut interrupts = 0;
+        for _ in 0..lines * LINE_DOTS as u32 / 4 {
+            interrupts |= ppu.tick(4);
+        }
+
+        interrupts
+    }
+
+    fn run_to_vblank(ppu: &mut Ppu) {
+        while ppu.mode != Mode::VBlank {
+            ppu.tick(4);
+        }
+    }
+
+    // Fills tile `index` at `base` with a solid color
+    fn solid_tile(ppu: &mut Ppu, base: u16, index: u8, color: u8) {
+        let lo = if color & 0x01 != 0 { 0xFF } else { 0x00 };
+        let hi = if color & 0x02 != 0 { 0xFF } else { 0x00 };
+
+        let start = (base - 0x8000) as usize + index as usize * 16;
+        for row in ppu.vram[start..start + 16].chunks_exact_mut(2) {
+            row.copy_from_slice(&[lo, hi]);
+        }
+    }
+
+    #[test]
+    fn test_mode_timing_within_a_line() {
+        for renderer in RENDERERS {
+            let mut ppu = enabled_ppu(renderer);
+            assert_eq!(ppu.mode, Mode::OamScan);
+
+            ppu.tick(76);
+            assert_eq!(ppu.mode, Mode::OamScan);
+            ppu.tick(4);
+            assert_eq!(ppu.mode, Mode::Drawing);
+
+            ppu.tick(172);
+            assert_eq!(ppu.mode, Mode::HBlank);
+            assert_eq!(ppu.read(STAT_ADDR) & 0x03, 0);
+
+            ppu.tick(200);
+            ppu.tick(4);
+            assert_eq!(ppu.read(LY_ADDR), 1);
+            assert_eq!(ppu.mode, Mode::OamScan);
+        }
+    }
+
+    #[test]
+    fn test_vblank_raised_after_144_lines() {
+        for renderer in RENDERERS {
+            let mut ppu = enabled_ppu(renderer);
+
+            let interrupts = run_lines(&mut ppu, 144);
+
+            assert_eq!(interrupts, Interrupt::VBlank.bit());
+            assert_eq!(ppu.read(LY_ADDR), 144);
+            assert_eq!(ppu.read(STAT_ADDR) & 0x03, 1);
+
+            run_lines(&mut ppu, 10);
+            assert_eq!(ppu.read(LY_ADDR), 0);
+            assert_eq!(ppu.mode, Mode::OamScan);
+        }
+    }
+
+    #[test]
+    fn test_lyc_coincidence() {
+        for renderer in RENDERERS {
+            let mut ppu = enabled_ppu(renderer);
+            ppu.write(LYC_ADDR, 2);
+            assert_eq!(ppu.read(STAT_ADDR) & 0x04, 0);
+
+            run_lines(&mut ppu, 2);
+
+            assert_eq!(ppu.read(STAT_ADDR) & 0x04, 0x04);
+        }
+    }
+
+    #[test]
+    fn test_lcd_off_resets_ly() {
+        for renderer in RENDERERS {
+            let mut ppu = enabled_ppu(renderer);
+            run_lines(&mut ppu, 3);
+
+            ppu.write(LCDC_ADDR, 0x11);
+            ppu.tick(200);
+
+            assert_eq!(ppu.read(LY_ADDR), 0);
+            assert_eq!(ppu.mode, Mode::HBlank);
+        }
+    }
+
+    #[test]
+    fn test_vram_locked_while_drawing() {
+        for renderer in RENDERERS {
+            let mut ppu = enabled_ppu(renderer);
+            ppu.vram[0] = 0x42;
+            ppu.tick(80);
+
+            ppu.write_vram(0x8000, 0x11);
+            assert_eq!(ppu.read_vram(0x8000), 0xFF);
+            assert_eq!(ppu.read_oam(0xFE00), 0xFF);
+
+            ppu.tick(172);
+            assert_eq!(ppu.read_vram(0x8000), 0x42);
+        }
+    }
+
+    #[test]
+    fn test_background_unsigned_tile_data() {
+        for renderer in RENDERERS {
+            let mut ppu = enabled_ppu(renderer);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 1, 3);
+            // Tile 1 at map position (1, 0)
+            ppu.vram[0x1801] = 1;
+
+            run_to_vblank(&mut ppu);
+
+            let frame = ppu.frame();
+            assert_eq!(frame[7], 0);
+            assert_eq!(frame[8], 3);
+            assert_eq!(frame[15], 3);
+            assert_eq!(frame[16], 0);
+            assert_eq!(frame[7 * SCREEN_WIDTH + 8], 3);
+            assert_eq!(frame[8 * SCREEN_WIDTH + 8], 0);
+        }
+    }
+
+    #[test]
+    fn test_background_signed_tile_data() {
+        for renderer in RENDERERS {
+            let mut ppu = enabled_ppu(renderer);
+            ppu.write(LCDC_ADDR, 0x81);
+            // Tile 0 lives at 0x9000 and tile 0xFF at 0x8FF0
+            solid_tile(&mut ppu, 0x9000, 0, 1);
+            solid_tile(&mut ppu, 0x8800, 0x7F, 2);
+            ppu.vram[0x1801] = 0xFF;
+
+            run_to_vblank(&mut ppu);
+
+            assert_eq!(ppu.frame()[0], 1);
+            assert_eq!(ppu.frame()[8], 2);
+        }
+    }
+
+    #[test]
+    fn test_background_scroll_wraps() {
+        for renderer in RENDERERS {
+            let mut ppu = enabled_ppu(renderer);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 1, 2);
+            // Bottom-right corner of the 256x256 map
+            ppu.vram[0x1BFF] = 1;
+            ppu.write(SCX_ADDR, 0xFC);
+            ppu.write(SCY_ADDR, 0xFC);
+
+            run_to_vblank(&mut ppu);
+
+            let frame = ppu.frame();
+            assert_eq!(frame[3], 2);
+            assert_eq!(frame[4], 0);
+            assert_eq!(frame[3 * SCREEN_WIDTH], 2);
+            assert_eq!(frame[4 * SCREEN_WIDTH], 0);
+        }
+    }
+
+    #[test]
+    fn test_bgp_maps_colors_to_shades() {
+        for renderer in RENDERERS {
+            let mut ppu = enabled_ppu(renderer);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 0, 1);
+            ppu.write(BGP_ADDR, 0b00_00_11_00);
+
+            run_to_vblank(&mut ppu);
+
+            assert!(ppu.frame().iter().all(|&shade| shade == 3));
+        }
+    }
+
+    #[test]
+    fn test_background_disabled_is_white() {
+        for renderer in RENDERERS {
+            let mut ppu = enabled_ppu(renderer);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 0, 3);
+            ppu.write(LCDC_ADDR, 0x90);
+
+            run_to_vblank(&mut ppu);
+
+            assert!(ppu.frame().iter().all(|&shade| shade == 0));
+        }
+    }
+
+    // Writes OAM entry `index`
+    fn put_sprite(ppu: &mut Ppu, index: usize, y: u8, x: u8, tile: u8, flags: u8) {
+        ppu.oam[index * 4..index * 4 + 4].copy_from_slice(&[y, x, tile, flags]);
+    }
+
+    fn sprite_ppu(renderer: Renderer) -> Ppu {
+        let mut ppu = enabled_ppu(renderer);
+        ppu.write(LCDC_ADDR, 0x93);
+        ppu.write(OBP0_ADDR, 0xE4);
+        ppu.write(OBP1_ADDR, 0x1B);
+
+        ppu
+    }
+
+    #[test]
+    fn test_sprite_drawn_at_offset_position() {
+        for renderer in RENDERERS {
+            let mut ppu = sprite_ppu(renderer);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 1, 3);
+            put_sprite(&mut ppu, 0, 16 + 10, 8 + 20, 1, 0x00);
+
+            run_to_vblank(&mut ppu);
+
+            let frame = ppu.frame();
+            assert_eq!(frame[10 * SCREEN_WIDTH + 19], 0);
+            assert_eq!(frame[10 * SCREEN_WIDTH + 20], 3);
+            assert_eq!(frame[17 * SCREEN_WIDTH + 27], 3);
+            assert_eq!(frame[18 * SCREEN_WIDTH + 20], 0);
+        }
+    }
+
+    #[test]
+    fn test_sprite_palettes() {
+        for renderer in RENDERERS {
+            let mut ppu = sprite_ppu(renderer);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 1, 1);
+            put_sprite(&mut ppu, 0, 16, 8, 1, 0x00);
+            put_sprite(&mut ppu, 1, 16, 16, 1, 0x10);
+
+            run_to_vblank(&mut ppu);
+
+            assert_eq!(ppu.frame()[0], 1);
+            assert_eq!(ppu.frame()[8], 2);
+        }
+    }
+
+    #[test]
+    fn test_ten_sprites_per_line() {
+        for renderer in RENDERERS {
+            let mut ppu = sprite_ppu(renderer);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 1, 3);
+            for i in 0..11 {
+                put_sprite(&mut ppu, i, 16, 8 + 8 * i as u8, 1, 0x00);
+            }
+
+            run_to_vblank(&mut ppu);
+
+            assert_eq!(ppu.frame()[9 * 8], 3);
+            assert_eq!(ppu.frame()[10 * 8], 0);
+        }
+    }
+
+    #[test]
+    fn test_smaller_x_wins_and_transparency_falls_through() {
+        for renderer in RENDERERS {
+            let mut ppu = sprite_ppu(renderer);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 1, 1);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 2, 2);
+            // Left half of tile 3 is transparent
+            for row in 0..8 {
+                ppu.vram[3 * 16 + row * 2] = 0x0F;
+                ppu.vram[3 * 16 + row * 2 + 1] = 0x0F;
+            }
+            put_sprite(&mut ppu, 0, 16, 12, 1, 0x00);
+            put_sprite(&mut ppu, 1, 16, 8, 3, 0x00);
+            put_sprite(&mut ppu, 2, 16, 8, 2, 0x00);
+
+            run_to_vblank(&mut ppu);
+
+            let frame = ppu.frame();
+            // Sprite 1 is transparent at 0-3, sprite 2 shares its X but comes later
+            assert_eq!(frame[0], 2);
+            assert_eq!(frame[4], 3);
+            // Sprite 0 has a larger X than sprites 1 and 2
+            assert_eq!(frame[8], 1);
+        }
+    }
+
+    #[test]
+    fn test_sprite_flips() {
+        for renderer in RENDERERS {
+            let mut ppu = sprite_ppu(renderer);
+            // Only the top-left pixel of tile 1 is set
+            ppu.vram[16] = 0x80;
+            ppu.vram[17] = 0x80;
+            put_sprite(&mut ppu, 0, 16, 8, 1, 0x60);
+
+            run_to_vblank(&mut ppu);
+
+            assert_eq!(ppu.frame()[0], 0);
+            assert_eq!(ppu.frame()[7 * SCREEN_WIDTH + 7], 3);
+        }
+    }
+
+    #[test]
+    fn test_tall_sprites_ignore_tile_bit_0() {
+        for renderer in RENDERERS {
+            let mut ppu = sprite_ppu(renderer);
+            ppu.write(LCDC_ADDR, 0x97);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 2, 1);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 3, 2);
+            put_sprite(&mut ppu, 0, 16, 8, 3, 0x00);
+            put_sprite(&mut ppu, 1, 16, 16, 3, 0x40);
+
+            run_to_vblank(&mut ppu);
+
+            let frame = ppu.frame();
+            assert_eq!(frame[0], 1);
+            assert_eq!(frame[15 * SCREEN_WIDTH], 2);
+            assert_eq!(frame[8], 2);
+            assert_eq!(frame[15 * SCREEN_WIDTH + 8], 1);
+            assert_eq!(frame[16 * SCREEN_WIDTH], 0);
+        }
+    }
+
+    #[test]
+    fn test_obj_size_change_mid_line_keeps_scanned_height() {
+        for renderer in RENDERERS {
+            let mut ppu = sprite_ppu(renderer);
+            ppu.write(LCDC_ADDR, 0x97);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 2, 1);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 3, 2);
+            put_sprite(&mut ppu, 0, 16, 8, 2, 0x40);
+
+            // Switch to 8x8 sprites while line 8 is being drawn
+            run_lines(&mut ppu, 8);
+            ppu.tick(OAM_SCAN_DOTS as u8 + 4);
+            ppu.write(LCDC_ADDR, 0x93);
+            run_to_vblank(&mut ppu);
+
+            // Row 8 of the flipped 8x16 sprite is row 7 of its top tile
+            assert_eq!(ppu.frame()[8 * SCREEN_WIDTH], 1, "{renderer:?}");
+            assert_eq!(ppu.frame()[9 * SCREEN_WIDTH], 0, "{renderer:?}");
+        }
+    }
+
+    #[test]
+    fn test_bg_priority_only_hides_behind_nonzero_colors() {
+        for renderer in RENDERERS {
+            let mut ppu = sprite_ppu(renderer);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 1, 2);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 2, 3);
+            ppu.vram[0x1801] = 2;
+            put_sprite(&mut ppu, 0, 16, 8, 1, 0x80);
+            put_sprite(&mut ppu, 1, 16, 16, 1, 0x80);
+
+            run_to_vblank(&mut ppu);
+
+            assert_eq!(ppu.frame()[0], 2);
+            assert_eq!(ppu.frame()[8], 3);
+        }
+    }
+
+    #[test]
+    fn test_sprites_disabled() {
+        for renderer in RENDERERS {
+            let mut ppu = sprite_ppu(renderer);
+            ppu.write(LCDC_ADDR, 0x91);
+            solid_tile(&mut ppu, TILE_DATA_ADDR, 1, 3);
+            put_sprite(&mut ppu, 0, 16, 8, 1, 0x00);
+
+            run_to_vblank(&mut ppu);
+
+            assert_eq!(ppu.frame()[0], 0);
+        }
+    }
+
+    // Window on with its map at 0x9C00: tile 1 (color 1) on map row 0 and
+    // tile 2 (color 2) on map row 1. The background stays color 0.
+    fn window_ppu(renderer: Renderer, wx: u8, wy: u8) -> Ppu {
+        let mut ppu = enabled_ppu(renderer);
+        ppu.write(LCDC_ADDR, 0xF1);
+        ppu.write(WX_ADDR, wx);
+        ppu.write(WY_ADDR, wy);
+        solid_tile(&mut ppu, TILE_DATA_ADDR, 1, 1);
+        solid_tile(&mut ppu, TILE_DATA_ADDR, 2, 2);
+        ppu.vram[0x1C00..0x1C20].fill(1);
+        ppu.vram[0x1C20..0x1C40].fill(2);
+
+        ppu
+    }
+
+    #[test]
+    fn test_window_position() {
+        for renderer in RENDERERS {
+            let mut ppu = window_ppu(renderer, 7 + 10, 5);
+
+            run_to_vblank(&mut ppu);
+
+            let frame = ppu.frame();
+            assert_eq!(frame[4 * SCREEN_WIDTH + 10], 0);
+            assert_eq!(frame[5 * SCREEN_WIDTH + 9], 0);
+            assert_eq!(frame[5 * SCREEN_WIDTH + 10], 1);
+            assert_eq!(frame[13 * SCREEN_WIDTH + 10], 2);
+        }
+    }
+
+    #[test]
+    fn test_window_line_counter_skips_hidden_lines() {
+        for renderer in RENDERERS {
+            let mut ppu = window_ppu(renderer, 7, 0);
+
+            run_lines(&mut ppu, 4);
+            ppu.write(LCDC_ADDR, 0xD1);
+            run_lines(&mut ppu, 6);
+            ppu.write(LCDC_ADDR, 0xF1);
+            run_to_vblank(&mut ppu);
+
+            let frame = ppu.frame();
+            // Line 10 draws window row 4 and line 14 row 8, not rows 10 and 14
+            assert_eq!(frame[5 * SCREEN_WIDTH], 0);
+            assert_eq!(frame[10 * SCREEN_WIDTH], 1);
+            assert_eq!(frame[13 * SCREEN_WIDTH], 1);
+            assert_eq!(frame[14 * SCREEN_WIDTH], 2);
+        }
+    }
+
+    #[test]
+    fn test_window_wx_below_7_cuts_left_edge() {
+        for renderer in RENDERERS {
+            let mut ppu = window_ppu(renderer, 0, 0);
+            // Only the rightmost column of tile 3 is set
+            for row in 0..8 {
+                ppu.vram[3 * 16 + row * 2] = 0x01;
+            }
+            ppu.vram[0x1C00] = 3;
+
+            run_to_vblank(&mut ppu);
+
+            assert_eq!(ppu.frame()[0], 1);
+            assert_eq!(ppu.frame()[1], 1);
+        }
+    }
+
+    #[test]
+    fn test_window_wx_166_covers_next_line() {
+        for renderer in RENDERERS {
+            let mut ppu = window_ppu(renderer, 166, 0);
+
+            run_to_vblank(&mut ppu);
+
+            let frame = ppu.frame();
+            assert_eq!(frame[SCREEN_WIDTH - 1], 0);
+            assert_eq!(frame[SCREEN_WIDTH], 1);
+            assert_eq!(frame[2 * SCREEN_WIDTH - 1], 1);
+        }
+    }
+
+    #[test]
+    fn test_window_hidden_when_bg_disabled() {
+        for renderer in RENDERERS {
+            let mut ppu = window_ppu(renderer, 7, 0);
+            ppu.write(LCDC_ADDR, 0xF0);
+
+            run_to_vblank(&mut ppu);
+
+            assert!(ppu.frame().iter().all(|&shade| shade == 0));
+        }
+    }
+
+    #[test]
+    fn test_window_line_counter_runs_while_bg_disabled() {
+        for renderer in RENDERERS {
+            let mut ppu = window_ppu(renderer, 7, 0);
+
+            ppu.write(LCDC_ADDR, 0xF0);
+            run_lines(&mut ppu, 4);
+            ppu.write(LCDC_ADDR, 0xF1);
+            run_to_vblank(&mut ppu);
+
+            // Blank lines 0-3 still used up window rows 0-3
+            let frame = ppu.frame();
+            assert_eq!(frame[4 * SCREEN_WIDTH], 1, "{renderer:?}");
+            assert_eq!(frame[8 * SCREEN_WIDTH], 2, "{renderer:?}");
+        }
+    }
+
+    // Length of mode 3 on the first line
+    fn drawing_dots(ppu: &mut Ppu) -> u16 {
+        ppu.tick(OAM_SCAN_DOTS as u8);
+        let mut dots = 0;
+        while ppu.mode == Mode::Drawing {
+            ppu.tick(1);
+            dots += 1;
+        }
+
+        dots
+    }
+
+    #[test]
+    fn test_fifo_drawing_length_depends_on_scx_window_and_sprites() {
+        let mut ppu = enabled_ppu(Renderer::PixelFifo);
+        ppu.write(SCX_ADDR, 3);
+        assert_eq!(drawing_dots(&mut ppu), DRAWING_DOTS + 3);
+
+        let mut ppu = window_ppu(Renderer::PixelFifo, 7 + 80, 0);
+        assert_eq!(drawing_dots(&mut ppu), DRAWING_DOTS + 6);
+
+        let mut ppu = sprite_ppu(Renderer::PixelFifo);
+        put_sprite(&mut ppu, 0, 16, 8 + 40, 0, 0x00);
+        put_sprite(&mut ppu, 1, 16, 8 + 80, 0, 0x00);
+        // Each sprite starts a tile, so waits 5 dots for the BG fetch before
+        // its own 6-dot fetch
+        assert_eq!(drawing_dots(&mut ppu), DRAWING_DOTS + 2 * (5 + 6));
+
+        let mut ppu = sprite_ppu(Renderer::PixelFifo);
+        put_sprite(&mut ppu, 0, 16, 8 + 45, 0, 0x00);
+        put_sprite(&mut ppu, 1, 16, 8 + 46, 0, 0x00);
+        // No wait from the 6th pixel of a tile, and only one per tile
+        assert_eq!(drawing_dots(&mut ppu), DRAWING_DOTS + 2 * 6);
+    }
+
+    #[test]
+    fn test_fifo_ignores_sprites_while_disabled() {
+        let mut ppu = sprite_ppu(Renderer::PixelFifo);
+        ppu.write(LCDC_ADDR, 0x91);
+        put_sprite(&mut ppu, 0, 16, 8 + 40, 0, 0x00);
+
+        assert_eq!(drawing_dots(&mut ppu), DRAWING_DOTS);
+    }
+
+    #[test]
+    fn test_fifo_shows_mid_line_palette_change() {
+        let mut ppu = enabled_ppu(Renderer::PixelFifo);
+        solid_tile(&mut ppu, TILE_DATA_ADDR, 0, 1);
+
+        // The first pixel leaves the FIFO 13 dots into mode 3, after the
+        // discarded and the first real tile fetch
+        ppu.tick(OAM_SCAN_DOTS as u8);
+        ppu.tick(12 + 80);
+        ppu.write(BGP_ADDR, 0xE0);
+        run_to_vblank(&mut ppu);
+
+        assert_eq!(ppu.frame()[79], 1);
+        assert_eq!(ppu.frame()[80], 0);
+        assert_eq!(ppu.frame()[SCREEN_WIDTH], 0);
+    }
+
+    #[test]
+    fn test_stat_interrupt_on_mode_changes() {
+        let mut ppu = enabled_ppu(Renderer::Scanline);
+        ppu.write(STAT_ADDR, STAT_HBLANK_SELECT);
+
+        assert_eq!(ppu.tick(80 + 168), 0);
+        assert_eq!(ppu.tick(4), Interrupt::LcdStat.bit());
+
+        let mut ppu = enabled_ppu(Renderer::Scanline);
+        ppu.write(STAT_ADDR, STAT_OAM_SELECT);
+        ppu.tick(252);
+        assert_eq!(ppu.tick(200), 0);
+        assert_eq!(ppu.tick(4), Interrupt::LcdStat.bit());
+
+        let mut ppu = enabled_ppu(Renderer::Scanline);
+        ppu.write(STAT_ADDR, STAT_VBLANK_SELECT);
+        let interrupts = run_lines(&mut ppu, 144);
+        assert_eq!(
+            interrupts,
+            Interrupt::VBlank.bit() | Interrupt::LcdStat.bit()
+        );
+    }
+
+    #[test]
+    fn test_stat_interrupt_on_lyc_match() {
+        let mut ppu = enabled_ppu(Renderer::Scanline);
+        ppu.write(STAT_ADDR, STAT_LYC_SELECT);
+        ppu.write(LYC_ADDR, 3);
+
+        assert_eq!(run_lines(&mut ppu, 2), 0);
+        assert_eq!(run_lines(&mut ppu, 1), Interrupt::LcdStat.bit());
+
+        // Writing LYC to the current line matches straight away
+        assert_eq!(ppu.write(LYC_ADDR, 5), 0);
+        assert_eq!(ppu.write(LYC_ADDR, 3), Interrupt::LcdStat.bit());
+    }
+
+    #[test]
+    fn test_stat_line_blocks_back_to_back_sources() {
+        let mut ppu = enabled_ppu(Renderer::Scanline);
+        ppu.write(STAT_ADDR, STAT_HBLANK_SELECT | STAT_OAM_SELECT);
+        ppu.tick(252);
+
+        // HBlank hands over to OAM scan without the line going low
+        assert_eq!(ppu.tick(204), 0);
+        assert_eq!(ppu.mode, Mode::OamScan);
+
+        // It drops during drawing, so the next HBlank raises it again
+        assert_eq!(ppu.tick(252), Interrupt::LcdStat.bit());
+    }
+
+    #[test]
+    fn test_stat_write_raises_spurious_interrupt() {
+        let mut ppu = enabled_ppu(Renderer::Scanline);
+        ppu.tick(80);
+        // Nothing is true while drawing on a line that does not match LYC
+        ppu.write(LYC_ADDR, 10);
+        assert_eq!(ppu.write(STAT_ADDR, 0x00), 0);
+
+        ppu.tick(172);
+        assert_eq!(ppu.write(STAT_ADDR, 0x00), Interrupt::LcdStat.bit());
+
+        run_to_vblank(&mut ppu);
+        assert_eq!(ppu.write(STAT_ADDR, 0x00), Interrupt::LcdStat.bit());
+    }
+
+    #[test]
+    fn test_no_stat_interrupt_while_lcd_off() {
+        let mut ppu = enabled_ppu(Renderer::Scanline);
+        ppu.write(LCDC_ADDR, 0x11);
+
+        assert_eq!(ppu.write(STAT_ADDR, 0x78), 0);
+        assert_eq!(ppu.tick(200), 0);
+    }
+}
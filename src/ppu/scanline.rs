@@ -0,0 +1,98 @@
+//! Fast renderer that draws each line in one go at the end of mode 3.
+//! Register writes made while a line is being drawn only show up from the
+//! next line on.
+
+use crate::ppu::{LCDC_BG_ENABLE, LCDC_OBJ_ENABLE, Ppu, SCREEN_WIDTH, tile};
+
+impl Ppu {
+    pub(super) fn render_line(&mut self) {
+        // Raw BG color indices, needed for sprite priority
+        let mut bg_colors = [0; SCREEN_WIDTH];
+
+        let map_base = self.bg_map_base();
+        let y = self.ly.wrapping_add(self.scy);
+
+        for (x, color) in bg_colors.iter_mut().enumerate() {
+            let x = (x as u8).wrapping_add(self.scx);
+            let tile = tile::tile_at(&self.vram, map_base, x, y);
+            *color = tile::tile_pixel(&self.vram, self.tile_addr(tile), x % 8, y % 8);
+        }
+
+        // The window is still drawn, and its line counter still advances,
+        // while LCDC bit 0 blanks both layers
+        self.draw_window(&mut bg_colors);
+
+        // On DMG a disabled background is drawn as white, not color 0
+        if self.lcdc & LCDC_BG_ENABLE == 0 {
+            bg_colors = [0; SCREEN_WIDTH];
+        }
+
+        let mut line = bg_colors.map(|color| tile::shade(self.bgp, color));
+
+        if self.lcdc & LCDC_OBJ_ENABLE != 0 {
+            self.draw_sprites(&mut line, &bg_colors);
+        }
+
+        let start = self.ly as usize * SCREEN_WIDTH;
+        self.back_buffer[start..start + SCREEN_WIDTH].copy_from_slice(&line);
+    }
+
+    // The window replaces the background from screen X = WX - 7 onwards.
+    // WX 0-6 cut off its left edge, and WX=166 starts it too late to show on
+    // this line, so it instead covers the whole of the next one.
+    fn draw_window(&mut self, bg_colors: &mut [u8; SCREEN_WIDTH]) {
+        let wrapped = std::mem::take(&mut self.window_wrap);
+        if !self.window_enabled() {
+            return;
+        }
+
+        if self.wx == 166 {
+            self.window_wrap = true;
+        }
+
+        let (start, skipped) = match self.wx {
+            _ if wrapped => (0, 0),
+            166.. => return,
+            wx => ((wx as usize).saturating_sub(7), 7u8.saturating_sub(wx)),
+        };
+
+        let map_base = self.window_map_base();
+        let y = self.window_line;
+
+        for (column, color) in bg_colors[start..].iter_mut().enumerate() {
+            let x = column as u8 + skipped;
+            let tile = tile::tile_at(&self.vram, map_base, x, y);
+            *color = tile::tile_pixel(&self.vram, self.tile_addr(tile), x % 8, y % 8);
+        }
+
+        self.window_line += 1;
+    }
+
+    fn draw_sprites(&self, line: &mut [u8; SCREEN_WIDTH], bg_colors: &[u8; SCREEN_WIDTH]) {
+        // On DMG the sprite with the smallest X wins, OAM order breaks ties
+        let mut sprites = self.line_sprites.clone();
+        sprites.sort_by_key(|sprite| (sprite.x, sprite.index));
+
+        for (x, pixel) in line.iter_mut().enumerate() {
+            // Transparent pixels of a higher priority sprite let the next one
+            // through
+            let Some((sprite, color)) = sprites.iter().find_map(|sprite| {
+                let color = sprite.pixel(&self.vram, x as u8, self.ly);
+                (color != 0).then_some((sprite, color))
+            }) else {
+                continue;
+            };
+
+            if sprite.behind_bg() && bg_colors[x] != 0 {
+                continue;
+            }
+
+            let palette = if sprite.uses_obp1() {
+                self.obp1
+            } else {
+                self.obp0
+            };
+            *pixel = tile::shade(palette, color);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::ppu::tile;
+
+/// Objects past this many on a line are dropped by the OAM scan.
+pub const MAX_SPRITES_PER_LINE: usize = 10;
+
+const FLAG_BG_PRIORITY: u8 = 0x80;
+const FLAG_Y_FLIP: u8 = 0x40;
+const FLAG_X_FLIP: u8 = 0x20;
+const FLAG_PALETTE: u8 = 0x10;
+
+/// One 4-byte OAM entry. Coordinates are stored as on hardware, offset by
+/// 16 (Y) and 8 (X) so sprites can be partially off screen.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Sprite {
+    pub y: u8,
+    pub x: u8,
+    pub tile: u8,
+    pub flags: u8,
+    // Position in OAM, breaks ties between sprites at the same X
+    pub index: u8,
+    // 8 or 16 as set by LCDC when the sprite was scanned, a later change
+    // during mode 3 must not move rows out of range
+    pub height: u8,
+}
+
+impl Sprite {
+    fn from_oam(oam: &[u8], index: usize, height: u8) -> Self {
+        let entry = &oam[index * 4..index * 4 + 4];
+
+        Sprite {
+            y: entry[0],
+            x: entry[1],
+            tile: entry[2],
+            flags: entry[3],
+            index: index as u8,
+            height,
+        }
+    }
+
+    /// BG and window colors 1-3 are drawn over this sprite.
+    pub fn behind_bg(&self) -> bool {
+        self.flags & FLAG_BG_PRIORITY != 0
+    }
+
+    /// OBP1 instead of OBP0.
+    pub fn uses_obp1(&self) -> bool {
+        self.flags & FLAG_PALETTE != 0
+    }
+
+    /// Color of this sprite at screen pixel (`x`, `ly`), 0 where it is
+    /// transparent or does not cover the pixel.
+    pub fn pixel(&self, vram: &[u8], x: u8, ly: u8) -> u8 {
+        let column = (x as u16 + 8).wrapping_sub(self.x as u16);
+        if column >= 8 {
+            return 0;
+        }
+
+        self.column_pixel(vram, column as u8, ly)
+    }
+
+    /// Color of column `column` (0 = leftmost before flipping) of this sprite
+    /// on line `ly`.
+    pub fn column_pixel(&self, vram: &[u8], column: u8, ly: u8) -> u8 {
+        let mut row = ly.wrapping_add(16).wrapping_sub(self.y);
+        if self.flags & FLAG_Y_FLIP != 0 {
+            row = self.height - 1 - row;
+        }
+        let column = if self.flags & FLAG_X_FLIP != 0 {
+            7 - column
+        } else {
+            column
+        };
+
+        // 8x16 sprites ignore bit 0 of the tile index, the bottom half is
+        // always the next tile
+        let tile = if self.height == 16 {
+            self.tile & 0xFE
+        } else {
+            self.tile
+        };
+        tile::tile_pixel(vram, tile as usize * 16, column, row)
+    }
+}
+
+/// Mode 2 OAM scan: the first ten sprites in OAM order whose rows cover
+/// line `ly`. X is not considered, so off-screen sprites still use a slot.
+pub fn scan_oam(oam: &[u8], ly: u8, height: u8) -> Vec<Sprite> {
+    let line = ly as u16 + 16;
+
+    (0..oam.len() / 4)
+        .map(|index| Sprite::from_oam(oam, index, height))
+        .filter(|sprite| (sprite.y as u16..sprite.y as u16 + height as u16).contains(&line))
+        .take(MAX_SPRITES_PER_LINE)
+        .collect()
+}
@@ -0,0 +1,21 @@
+//! Tile map and tile data decoding shared by the renderers.
+
+/// Tile index at pixel (`x`, `y`) of the 32x32 tile map at `map_base`.
+pub fn tile_at(vram: &[u8], map_base: usize, x: u8, y: u8) -> u8 {
+    vram[map_base + (y as usize / 8) * 32 + x as usize / 8]
+}
+
+/// 2-bit color of pixel (`x`, `y`) within the tile at `tile_addr`.
+/// Each row is two bytes, low bit plane first, leftmost pixel in bit 7.
+pub fn tile_pixel(vram: &[u8], tile_addr: usize, x: u8, y: u8) -> u8 {
+    let lo = vram[tile_addr + y as usize * 2];
+    let hi = vram[tile_addr + y as usize * 2 + 1];
+    let bit = 7 - x;
+
+    ((hi >> bit) & 0x01) << 1 | (lo >> bit) & 0x01
+}
+
+/// Looks up `color` in a BGP/OBP-style palette.
+pub fn shade(palette: u8, color: u8) -> u8 {
+    (palette >> (color * 2)) & 0x03
+}
@@ -26,6 +26,7 @@ pub enum Reg {
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Reg16 {
+    AF,
     BC,
     DE,
     SP,
@@ -50,6 +51,33 @@ impl RegFlags {
     }
 }
 
+impl Default for RegFlags {
+    fn default() -> Self {
+        RegFlags::new()
+    }
+}
+
+impl From<u8> for RegFlags {
+    // Bits 7..4 hold Z N H C, the lower nibble is ignored
+    fn from(value: u8) -> Self {
+        RegFlags {
+            zero: value & 0x80 != 0,
+            subtract: value & 0x40 != 0,
+            half_carry: value & 0x20 != 0,
+            carry: value & 0x10 != 0,
+        }
+    }
+}
+
+impl From<&RegFlags> for u8 {
+    fn from(flags: &RegFlags) -> Self {
+        (flags.zero as u8) << 7
+            | (flags.subtract as u8) << 6
+            | (flags.half_carry as u8) << 5
+            | (flags.carry as u8) << 4
+    }
+}
+
 pub enum Flag {
     Z,
     N,
@@ -82,7 +110,6 @@ impl Registers {
             Reg::E => self.e,
             Reg::H => self.h,
             Reg::L => self.l,
-            _ => unreachable!(),
         }
     }
 
@@ -95,7 +122,6 @@ impl Registers {
             Reg::E => self.e = value,
             Reg::H => self.h = value,
             Reg::L => self.l = value,
-            _ => unreachable!(),
         }
     }
 
@@ -104,7 +130,8 @@ impl Registers {
             Reg16::BC => self.read_bc(),
             Reg16::DE => self.read_de(),
             Reg16::HL => self.read_hl(),
-            _ => panic!("Cannot read 8-bit register as 16-bit"),
+            Reg16::AF => self.read_af(),
+            Reg16::SP => self.sp,
         }
     }
 
@@ -113,8 +140,8 @@ impl Registers {
             Reg16::BC => self.write_bc(value),
             Reg16::DE => self.write_de(value),
             Reg16::HL => self.write_hl(value),
+            Reg16::AF => self.write_af(value),
             Reg16::SP => self.sp = value,
-            _ => unreachable!(),
         }
     }
 
@@ -142,6 +169,25 @@ impl Registers {
         u16::from_be_bytes([self.d, self.e])
     }
 
+    pub fn write_af(&mut self, value: u16) {
+        let (a, f) = utils::split_hi_lo(value);
+        self.a = a;
+        self.f = RegFlags::from(f);
+    }
+
+    pub fn read_af(&self) -> u16 {
+        u16::from_be_bytes([self.a, u8::from(&self.f)])
+    }
+
+    pub fn get_flag(&self, flag: Flag) -> bool {
+        match flag {
+            Flag::Z => self.f.zero,
+            Flag::N => self.f.subtract,
+            Flag::H => self.f.half_carry,
+            Flag::C => self.f.carry,
+        }
+    }
+
     pub fn set_flag(&mut self, flag: Flag, state: bool) {
         match flag {
             Flag::Z => {
@@ -160,10 +206,15 @@ impl Registers {
     }
 }
 
+impl Default for Registers {
+    fn default() -> Self {
+        Registers::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Registers;
-    use crate::registers::RegFlags;
+    use super::{Flag, Reg16, RegFlags, Registers};
     use crate::utils;
 
     #[test]
@@ -241,4 +292,55 @@ mod tests {
 
         assert_eq!(de, 0x1234)
     }
+
+    #[test]
+    fn test_write_af_masks_low_nibble() {
+        let mut reg = Registers::new();
+
+        reg.write_af(0x12FF);
+
+        assert_eq!(reg.a, 0x12);
+        assert_eq!(reg.read_af(), 0x12F0);
+    }
+
+    #[test]
+    fn test_read_reg16_sp() {
+        let mut reg = Registers::new();
+
+        reg.sp = 0xFFFE;
+
+        assert_eq!(reg.read_reg16(Reg16::SP), 0xFFFE);
+    }
+
+    #[test]
+    fn test_reg_flags_to_u8() {
+        let mut flags = RegFlags::new();
+        flags.zero = true;
+        flags.carry = true;
+
+        assert_eq!(u8::from(&flags), 0x90);
+    }
+
+    #[test]
+    fn test_reg_flags_from_u8() {
+        let flags = RegFlags::from(0x6F);
+
+        assert!(!flags.zero);
+        assert!(flags.subtract);
+        assert!(flags.half_carry);
+        assert!(!flags.carry);
+        assert_eq!(u8::from(&flags), 0x60);
+    }
+
+    #[test]
+    fn test_get_flag() {
+        let mut reg = Registers::new();
+
+        reg.set_flag(Flag::H, true);
+
+        assert!(reg.get_flag(Flag::H));
+        assert!(!reg.get_flag(Flag::Z));
+        assert!(!reg.get_flag(Flag::N));
+        assert!(!reg.get_flag(Flag::C));
+    }
 }
@@ -0,0 +1,462 @@
+use std::{fmt, fs, io, path::Path};
+
+pub const LOGO_ADDR: usize = 0x104;
+pub const NINTENDO_LOGO: [u8; 48] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+    0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
+    0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+
+const HEADER_END: usize = 0x150;
+const TITLE_START: usize = 0x134;
+const TITLE_END: usize = 0x144;
+const NEW_LICENSEE_ADDR: usize = 0x144;
+const CGB_FLAG_ADDR: usize = 0x143;
+const SGB_FLAG_ADDR: usize = 0x146;
+const CARTRIDGE_TYPE_ADDR: usize = 0x147;
+const ROM_SIZE_ADDR: usize = 0x148;
+const RAM_SIZE_ADDR: usize = 0x149;
+const OLD_LICENSEE_ADDR: usize = 0x14B;
+const VERSION_ADDR: usize = 0x14C;
+const HEADER_CHECKSUM_ADDR: usize = 0x14D;
+const GLOBAL_CHECKSUM_ADDR: usize = 0x14E;
+
+#[derive(Debug)]
+pub enum CartridgeError {
+    Io(io::Error),
+    /// The file is too small to contain a header.
+    TooSmall(usize),
+    /// The file is smaller than the ROM size declared in the header.
+    Truncated {
+        expected: usize,
+        actual: usize,
+    },
+    UnknownCartridgeType(u8),
+    UnknownRomSize(u8),
+    UnknownRamSize(u8),
+    HeaderChecksum {
+        expected: u8,
+        actual: u8,
+    },
+    GlobalChecksum {
+        expected: u16,
+        actual: u16,
+    },
+    /// The header is valid but the mapper is not emulated.
+    UnsupportedMbc(Mbc),
+}
+
+impl fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CartridgeError::Io(err) => write!(f, "could not read ROM: {err}"),
+            CartridgeError::TooSmall(len) => write!(
+                f,
+                "ROM is {len} bytes, too small to contain a header ({HEADER_END} bytes)"
+            ),
+            CartridgeError::Truncated { expected, actual } => write!(
+                f,
+                "ROM is truncated: header declares {expected} bytes but file has {actual}"
+            ),
+            CartridgeError::UnknownCartridgeType(code) => {
+                write!(f, "unknown cartridge type {code:#04X}")
+            }
+            CartridgeError::UnknownRomSize(code) => write!(f, "unknown ROM size code {code:#04X}"),
+            CartridgeError::UnknownRamSize(code) => write!(f, "unknown RAM size code {code:#04X}"),
+            CartridgeError::HeaderChecksum { expected, actual } => write!(
+                f,
+                "header checksum mismatch: header says {expected:#04X}, computed {actual:#04X}"
+            ),
+            CartridgeError::GlobalChecksum { expected, actual } => write!(
+                f,
+                "global checksum mismatch: header says {expected:#06X}, computed {actual:#06X}"
+            ),
+            CartridgeError::UnsupportedMbc(mbc) => write!(f, "unsupported mapper {mbc:?}"),
+        }
+    }
+}
+
+impl std::error::Error for CartridgeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CartridgeError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CartridgeError {
+    fn from(err: io::Error) -> Self {
+        CartridgeError::Io(err)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Mbc {
+    None,
+    Mbc1,
+    Mbc2,
+    Mmm01,
+    Mbc3,
+    Mbc5,
+    Mbc6,
+    Mbc7,
+    PocketCamera,
+    Tama5,
+    HuC3,
+    HuC1,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CartridgeType {
+    pub mbc: Mbc,
+    pub ram: bool,
+    pub battery: bool,
+    pub timer: bool,
+    pub rumble: bool,
+}
+
+impl CartridgeType {
+    const fn new(mbc: Mbc, ram: bool, battery: bool, timer: bool, rumble: bool) -> Self {
+        CartridgeType {
+            mbc,
+            ram,
+            battery,
+            timer,
+            rumble,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        let cartridge_type = match code {
+            0x00 => CartridgeType::new(Mbc::None, false, false, false, false),
+            0x01 => CartridgeType::new(Mbc::Mbc1, false, false, false, false),
+            0x02 => CartridgeType::new(Mbc::Mbc1, true, false, false, false),
+            0x03 => CartridgeType::new(Mbc::Mbc1, true, true, false, false),
+            // MBC2 RAM lives on the chip itself and is not declared in the header
+            0x05 => CartridgeType::new(Mbc::Mbc2, false, false, false, false),
+            0x06 => CartridgeType::new(Mbc::Mbc2, false, true, false, false),
+            0x08 => CartridgeType::new(Mbc::None, true, false, false, false),
+            0x09 => CartridgeType::new(Mbc::None, true, true, false, false),
+            0x0B => CartridgeType::new(Mbc::Mmm01, false, false, false, false),
+            0x0C => CartridgeType::new(Mbc::Mmm01, true, false, false, false),
+            0x0D => CartridgeType::new(Mbc::Mmm01, true, true, false, false),
+            0x0F => CartridgeType::new(Mbc::Mbc3, false, true, true, false),
+            0x10 => CartridgeType::new(Mbc::Mbc3, true, true, true, false),
+            0x11 => CartridgeType::new(Mbc::Mbc3, false, false, false, false),
+            0x12 => CartridgeType::new(Mbc::Mbc3, true, false, false, false),
+            0x13 => CartridgeType::new(Mbc::Mbc3, true, true, false, false),
+            0x19 => CartridgeType::new(Mbc::Mbc5, false, false, false, false),
+            0x1A => CartridgeType::new(Mbc::Mbc5, true, false, false, false),
+            0x1B => CartridgeType::new(Mbc::Mbc5, true, true, false, false),
+            0x1C => CartridgeType::new(Mbc::Mbc5, false, false, false, true),
+            0x1D => CartridgeType::new(Mbc::Mbc5, true, false, false, true),
+            0x1E => CartridgeType::new(Mbc::Mbc5, true, true, false, true),
+            0x20 => CartridgeType::new(Mbc::Mbc6, false, false, false, false),
+            0x22 => CartridgeType::new(Mbc::Mbc7, true, true, false, true),
+            0xFC => CartridgeType::new(Mbc::PocketCamera, false, false, false, false),
+            0xFD => CartridgeType::new(Mbc::Tama5, false, false, false, false),
+            0xFE => CartridgeType::new(Mbc::HuC3, false, false, false, false),
+            0xFF => CartridgeType::new(Mbc::HuC1, true, true, false, false),
+            _ => return None,
+        };
+
+        Some(cartridge_type)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CgbSupport {
+    None,
+    Compatible,
+    Only,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Licensee {
+    Old(u8),
+    New(String),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Header {
+    pub title: String,
+    pub cgb: CgbSupport,
+    pub sgb: bool,
+    pub cartridge_type: CartridgeType,
+    pub rom_size: usize,
+    pub ram_size: usize,
+    pub licensee: Licensee,
+    pub version: u8,
+    pub header_checksum: u8,
+    pub global_checksum: u16,
+}
+
+impl Header {
+    pub fn parse(rom: &[u8]) -> Result<Self, CartridgeError> {
+        if rom.len() < HEADER_END {
+            return Err(CartridgeError::TooSmall(rom.len()));
+        }
+
+        let header_checksum = rom[HEADER_CHECKSUM_ADDR];
+        let actual = header_checksum_of(rom);
+        if actual != header_checksum {
+            return Err(CartridgeError::HeaderChecksum {
+                expected: header_checksum,
+                actual,
+            });
+        }
+
+        let cgb = match rom[CGB_FLAG_ADDR] {
+            0xC0 => CgbSupport::Only,
+            0x80 => CgbSupport::Compatible,
+            _ => CgbSupport::None,
+        };
+
+        // On CGB carts the last byte of the title area holds the CGB flag
+        let title_end = match cgb {
+            CgbSupport::None => TITLE_END,
+            _ => CGB_FLAG_ADDR,
+        };
+        let title = rom[TITLE_START..title_end]
+            .iter()
+            .take_while(|&&byte| byte != 0)
+            .map(|&byte| byte as char)
+            .collect();
+
+        let code = rom[CARTRIDGE_TYPE_ADDR];
+        let cartridge_type =
+            CartridgeType::from_code(code).ok_or(CartridgeError::UnknownCartridgeType(code))?;
+
+        let code = rom[ROM_SIZE_ADDR];
+        let rom_size = match code {
+            0x00..=0x08 => 0x8000 << code,
+            _ => return Err(CartridgeError::UnknownRomSize(code)),
+        };
+
+        let code = rom[RAM_SIZE_ADDR];
+        let ram_size = match code {
+            0x00 => 0,
+            0x01 => 0x800,
+            0x02 => 0x2000,
+            0x03 => 0x8000,
+            0x04 => 0x20000,
+            0x05 => 0x10000,
+            _ => return Err(CartridgeError::UnknownRamSize(code)),
+        };
+
+        let licensee = match rom[OLD_LICENSEE_ADDR] {
+            0x33 => Licensee::New(
+                rom[NEW_LICENSEE_ADDR..NEW_LICENSEE_ADDR + 2]
+                    .iter()
+                    .map(|&byte| byte as char)
+                    .collect(),
+            ),
+            code => Licensee::Old(code),
+        };
+
+        Ok(Header {
+            title,
+            cgb,
+            sgb: rom[SGB_FLAG_ADDR] == 0x03,
+            cartridge_type,
+            rom_size,
+            ram_size,
+            licensee,
+            version: rom[VERSION_ADDR],
+            header_checksum,
+            global_checksum: u16::from_be_bytes([
+                rom[GLOBAL_CHECKSUM_ADDR],
+                rom[GLOBAL_CHECKSUM_ADDR + 1],
+            ]),
+        })
+    }
+}
+
+pub struct Cartridge {
+    pub header: Header,
+    pub rom: Vec<u8>,
+}
+
+impl Cartridge {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CartridgeError> {
+        Cartridge::from_bytes(fs::read(path)?)
+    }
+
+    pub fn from_bytes(rom: Vec<u8>) -> Result<Self, CartridgeError> {
+        let header = Header::parse(&rom)?;
+
+        if rom.len() < header.rom_size {
+            return Err(CartridgeError::Truncated {
+                expected: header.rom_size,
+                actual: rom.len(),
+            });
+        }
+
+        Ok(Cartridge { header, rom })
+    }
+
+    /// The boot ROM never checks the global checksum and plenty of homebrew
+    /// gets it wrong, so it is only verified on request.
+    pub fn verify_global_checksum(&self) -> Result<(), CartridgeError> {
+        let actual = global_checksum_of(&self.rom);
+
+        if actual != self.header.global_checksum {
+            return Err(CartridgeError::GlobalChecksum {
+                expected: self.header.global_checksum,
+                actual,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn header_checksum_of(rom: &[u8]) -> u8 {
+    rom[TITLE_START..HEADER_CHECKSUM_ADDR]
+        .iter()
+        .fold(0u8, |sum, &byte| sum.wrapping_sub(byte).wrapping_sub(1))
+}
+
+fn global_checksum_of(rom: &[u8]) -> u16 {
+    rom.iter()
+        .enumerate()
+        .filter(|&(addr, _)| addr != GLOBAL_CHECKSUM_ADDR && addr != GLOBAL_CHECKSUM_ADDR + 1)
+        .fold(0u16, |sum, (_, &byte)| sum.wrapping_add(byte as u16))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Builds a minimal ROM with valid checksums.
+    pub fn build_rom(cartridge_type: u8, rom_size: u8, ram_size: u8) -> Vec<u8> {
+        let mut rom = vec![0; 0x8000 << rom_size];
+        rom[TITLE_START..TITLE_START + 4].copy_from_slice(b"TEST");
+        rom[CARTRIDGE_TYPE_ADDR] = cartridge_type;
+        rom[ROM_SIZE_ADDR] = rom_size;
+        rom[RAM_SIZE_ADDR] = ram_size;
+        fix_checksums(&mut rom);
+
+        rom
+    }
+
+    pub fn fix_checksums(rom: &mut [u8]) {
+        rom[HEADER_CHECKSUM_ADDR] = header_checksum_of(rom);
+        let [hi, lo] = global_checksum_of(rom).to_be_bytes();
+        rom[GLOBAL_CHECKSUM_ADDR] = hi;
+        rom[GLOBAL_CHECKSUM_ADDR + 1] = lo;
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let mut rom = build_rom(0x13, 0x02, 0x03);
+        rom[CGB_FLAG_ADDR] = 0x80;
+        rom[SGB_FLAG_ADDR] = 0x03;
+        rom[OLD_LICENSEE_ADDR] = 0x33;
+        rom[NEW_LICENSEE_ADDR..NEW_LICENSEE_ADDR + 2].copy_from_slice(b"01");
+        rom[VERSION_ADDR] = 0x01;
+        fix_checksums(&mut rom);
+
+        let cart = Cartridge::from_bytes(rom).unwrap();
+
+        assert_eq!(cart.header.title, "TEST");
+        assert_eq!(cart.header.cgb, CgbSupport::Compatible);
+        assert!(cart.header.sgb);
+        assert_eq!(cart.header.cartridge_type.mbc, Mbc::Mbc3);
+        assert!(cart.header.cartridge_type.ram);
+        assert!(cart.header.cartridge_type.battery);
+        assert!(!cart.header.cartridge_type.timer);
+        assert_eq!(cart.header.rom_size, 0x20000);
+        assert_eq!(cart.header.ram_size, 0x8000);
+        assert_eq!(cart.header.licensee, Licensee::New("01".to_string()));
+        assert_eq!(cart.header.version, 0x01);
+        assert!(cart.verify_global_checksum().is_ok());
+    }
+
+    #[test]
+    fn test_title_excludes_cgb_flag() {
+        let mut rom = build_rom(0x00, 0x00, 0x00);
+        rom[TITLE_START..TITLE_END].copy_from_slice(b"ABCDEFGHIJKLMNOP");
+        rom[CGB_FLAG_ADDR] = 0xC0;
+        fix_checksums(&mut rom);
+
+        let header = Header::parse(&rom).unwrap();
+
+        assert_eq!(header.title, "ABCDEFGHIJKLMNO");
+        assert_eq!(header.cgb, CgbSupport::Only);
+    }
+
+    #[test]
+    fn test_too_small() {
+        let result = Cartridge::from_bytes(vec![0; 0x100]);
+
+        assert!(matches!(result, Err(CartridgeError::TooSmall(0x100))));
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut rom = build_rom(0x01, 0x01, 0x00);
+        rom.truncate(0x8000);
+
+        let result = Cartridge::from_bytes(rom);
+
+        assert!(matches!(
+            result,
+            Err(CartridgeError::Truncated {
+                expected: 0x10000,
+                actual: 0x8000
+            })
+        ));
+    }
+
+    #[test]
+    fn test_bad_header_checksum() {
+        let mut rom = build_rom(0x00, 0x00, 0x00);
+        rom[HEADER_CHECKSUM_ADDR] ^= 0xFF;
+
+        let result = Cartridge::from_bytes(rom);
+
+        assert!(matches!(result, Err(CartridgeError::HeaderChecksum { .. })));
+    }
+
+    #[test]
+    fn test_unknown_codes() {
+        let mut rom = build_rom(0x00, 0x00, 0x00);
+        rom[CARTRIDGE_TYPE_ADDR] = 0x04;
+        fix_checksums(&mut rom);
+        assert!(matches!(
+            Header::parse(&rom),
+            Err(CartridgeError::UnknownCartridgeType(0x04))
+        ));
+
+        let mut rom = build_rom(0x00, 0x00, 0x00);
+        rom[ROM_SIZE_ADDR] = 0x52;
+        fix_checksums(&mut rom);
+        assert!(matches!(
+            Header::parse(&rom),
+            Err(CartridgeError::UnknownRomSize(0x52))
+        ));
+
+        let mut rom = build_rom(0x00, 0x00, 0x00);
+        rom[RAM_SIZE_ADDR] = 0x06;
+        fix_checksums(&mut rom);
+        assert!(matches!(
+            Header::parse(&rom),
+            Err(CartridgeError::UnknownRamSize(0x06))
+        ));
+    }
+
+    #[test]
+    fn test_bad_global_checksum() {
+        let mut rom = build_rom(0x00, 0x00, 0x00);
+        rom[0x7FFF] = 0x01;
+
+        let cart = Cartridge::from_bytes(rom).unwrap();
+
+        assert!(matches!(
+            cart.verify_global_checksum(),
+            Err(CartridgeError::GlobalChecksum { .. })
+        ));
+    }
+}
@@ -0,0 +1,140 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The `.sav` file backing a battery-backed cartridge.
+pub struct SaveFile {
+    path: PathBuf,
+    pub flush_interval: Duration,
+    last_flush: Instant,
+}
+
+impl SaveFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        SaveFile {
+            path: path.into(),
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            last_flush: Instant::now(),
+        }
+    }
+
+    /// The save file next to a ROM, `game.gb` saves to `game.sav`.
+    pub fn for_rom(rom_path: impl AsRef<Path>) -> Self {
+        SaveFile::new(rom_path.as_ref().with_extension("sav"))
+    }
+
+    pub fn with_flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns `None` when no save exists yet.
+    pub fn read(&self) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(&self.path) {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes to a temporary file first and renames it over the save, so a
+    /// crash mid-write leaves the previous save intact. A failed write still
+    /// counts as a flush, so it is retried once per interval, not on every step.
+    pub fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.last_flush = Instant::now();
+        let tmp_path = self.path.with_extension("sav.tmp");
+
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &self.path)?;
+
+        Ok(())
+    }
+
+    pub fn is_flush_due(&self) -> bool {
+        self.last_flush.elapsed() >= self.flush_interval
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::SaveFile;
+    use std::{
+        env, fs,
+        path::PathBuf,
+        time::{Duration, Instant},
+    };
+
+    /// A fresh, empty directory under the system temp dir.
+    pub fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("crusty-boy-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    /// Moves the last flush a whole interval back, so a flush is due right away.
+    pub fn backdate_flush(save: &mut SaveFile) {
+        save.last_flush = Instant::now() - save.flush_interval;
+    }
+
+    #[test]
+    fn test_for_rom_replaces_extension() {
+        let save = SaveFile::for_rom("roms/game.gb");
+
+        assert_eq!(save.path(), PathBuf::from("roms/game.sav"));
+    }
+
+    #[test]
+    fn test_read_missing_save() {
+        let dir = temp_dir("missing");
+        let save = SaveFile::new(dir.join("game.sav"));
+
+        assert_eq!(save.read().unwrap(), None);
+    }
+
+    #[test]
+    fn test_write_then_read() {
+        let dir = temp_dir("round-trip");
+        let mut save = SaveFile::new(dir.join("game.sav"));
+
+        save.write(&[1, 2, 3]).unwrap();
+        save.write(&[4, 5]).unwrap();
+
+        assert_eq!(save.read().unwrap(), Some(vec![4, 5]));
+        assert!(!dir.join("game.sav.tmp").exists());
+    }
+
+    #[test]
+    fn test_flush_interval() {
+        let dir = temp_dir("interval");
+        let mut save = SaveFile::new(dir.join("game.sav")).with_flush_interval(Duration::ZERO);
+        assert!(save.is_flush_due());
+
+        save.flush_interval = Duration::from_secs(3600);
+        save.write(&[0]).unwrap();
+        assert!(!save.is_flush_due());
+    }
+
+    #[test]
+    fn test_failed_write_waits_for_next_interval() {
+        let dir = temp_dir("failed-write");
+        let mut save = SaveFile::new(dir.join("missing").join("game.sav"));
+        backdate_flush(&mut save);
+        assert!(save.is_flush_due());
+
+        assert!(save.write(&[0]).is_err());
+        assert!(!save.is_flush_due());
+    }
+}
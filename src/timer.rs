@@ -0,0 +1,236 @@
+pub const DIV_ADDR: u16 = 0xFF04;
+pub const TIMA_ADDR: u16 = 0xFF05;
+pub const TMA_ADDR: u16 = 0xFF06;
+pub const TAC_ADDR: u16 = 0xFF07;
+
+/// The DMG timer. DIV is the upper byte of a 16-bit counter incremented every
+/// T-cycle, and TIMA is clocked by a falling edge on one of its bits (selected
+/// by TAC) ANDed with the TAC enable bit. Modelling the edge detector rather
+/// than a frequency counter gives the hardware glitches for free: resetting
+/// DIV or rewriting TAC while the selected bit is high also increments TIMA.
+pub struct Timer {
+    div: u16,
+    tima: u8,
+    tma: u8,
+    tac: u8,
+    // TIMA overflowed during the last M-cycle and still reads 0x00,
+    // TMA is loaded and the interrupt raised on the next one
+    overflow_pending: bool,
+    // TIMA was reloaded from TMA during the current M-cycle, writes to TIMA
+    // are ignored and writes to TMA go through to TIMA as well
+    reloading: bool,
+}
+
+impl Timer {
+    pub fn new() -> Self {
+        Timer::with_divider(0)
+    }
+
+    pub fn with_divider(div: u16) -> Self {
+        Timer {
+            div,
+            tima: 0,
+            tma: 0,
+            tac: 0,
+            overflow_pending: false,
+            reloading: false,
+        }
+    }
+
+    /// Advances the timer by `cycles` T-cycles, a multiple of 4.
+    /// Returns true if the timer interrupt was raised.
+    pub fn tick(&mut self, cycles: u8) -> bool {
+        let mut interrupt = false;
+        for _ in 0..cycles / 4 {
+            interrupt |= self.tick_m_cycle();
+        }
+
+        interrupt
+    }
+
+    fn tick_m_cycle(&mut self) -> bool {
+        self.reloading = false;
+
+        let interrupt = self.overflow_pending;
+        if self.overflow_pending {
+            self.overflow_pending = false;
+            self.reloading = true;
+            self.tima = self.tma;
+        }
+
+        let signal = self.signal();
+        self.div = self.div.wrapping_add(4);
+        self.detect_falling_edge(signal);
+
+        interrupt
+    }
+
+    // Output of the multiplexer feeding the edge detector
+    fn signal(&self) -> bool {
+        let bit = match self.tac & 0x03 {
+            0b00 => 9,
+            0b01 => 3,
+            0b10 => 5,
+            _ => 7,
+        };
+
+        self.tac & 0x04 != 0 && self.div & (1 << bit) != 0
+    }
+
+    fn detect_falling_edge(&mut self, old_signal: bool) {
+        if !old_signal || self.signal() {
+            return;
+        }
+
+        let (tima, overflow) = self.tima.overflowing_add(1);
+        self.tima = tima;
+        self.overflow_pending |= overflow;
+    }
+
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            DIV_ADDR => (self.div >> 8) as u8,
+            TIMA_ADDR => self.tima,
+            TMA_ADDR => self.tma,
+            _ => self.tac | 0xF8,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, value: u8) {
+        let signal = self.signal();
+
+        match addr {
+            DIV_ADDR => self.div = 0,
+            TIMA_ADDR => {
+                if !self.reloading {
+                    self.tima = value;
+                    // Writing during the delay cancels the reload and interrupt
+                    self.overflow_pending = false;
+                }
+            }
+            TMA_ADDR => {
+                self.tma = value;
+                if self.reloading {
+                    self.tima = value;
+                }
+            }
+            _ => self.tac = value & 0x07,
+        }
+
+        self.detect_falling_edge(signal);
+    }
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Timer::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled_timer(tac: u8) -> Timer {
+        let mut timer = Timer::new();
+        timer.write(TAC_ADDR, tac);
+
+        timer
+    }
+
+    #[test]
+    fn test_div_increments_every_256_cycles() {
+        let mut timer = Timer::new();
+
+        timer.tick(252);
+        assert_eq!(timer.read(DIV_ADDR), 0);
+
+        timer.tick(4);
+        assert_eq!(timer.read(DIV_ADDR), 1);
+    }
+
+    #[test]
+    fn test_tac_selects_frequency() {
+        for (tac, period) in [(0x04, 1024), (0x05, 16), (0x06, 64), (0x07, 256)] {
+            let mut timer = enabled_timer(tac);
+
+            for _ in 0..period / 4 - 1 {
+                timer.tick(4);
+            }
+            assert_eq!(timer.read(TIMA_ADDR), 0, "TAC {tac:#04X}");
+
+            timer.tick(4);
+            assert_eq!(timer.read(TIMA_ADDR), 1, "TAC {tac:#04X}");
+        }
+    }
+
+    #[test]
+    fn test_disabled_timer_does_not_count() {
+        let mut timer = enabled_timer(0x01);
+
+        timer.tick(64);
+
+        assert_eq!(timer.read(TIMA_ADDR), 0);
+        assert_eq!(timer.read(TAC_ADDR), 0xF9);
+    }
+
+    #[test]
+    fn test_overflow_reloads_after_one_m_cycle() {
+        let mut timer = enabled_timer(0x05);
+        timer.write(TMA_ADDR, 0xAB);
+        timer.write(TIMA_ADDR, 0xFF);
+
+        assert!(!timer.tick(16));
+        assert_eq!(timer.read(TIMA_ADDR), 0x00);
+
+        assert!(timer.tick(4));
+        assert_eq!(timer.read(TIMA_ADDR), 0xAB);
+    }
+
+    #[test]
+    fn test_tima_write_during_delay_cancels_reload() {
+        let mut timer = enabled_timer(0x05);
+        timer.write(TMA_ADDR, 0xAB);
+        timer.write(TIMA_ADDR, 0xFF);
+        timer.tick(16);
+
+        timer.write(TIMA_ADDR, 0x12);
+
+        assert!(!timer.tick(4));
+        assert_eq!(timer.read(TIMA_ADDR), 0x12);
+    }
+
+    #[test]
+    fn test_writes_on_reload_cycle() {
+        let mut timer = enabled_timer(0x05);
+        timer.write(TIMA_ADDR, 0xFF);
+        timer.tick(20);
+
+        timer.write(TIMA_ADDR, 0x12);
+        assert_eq!(timer.read(TIMA_ADDR), 0x00);
+
+        timer.write(TMA_ADDR, 0x34);
+        assert_eq!(timer.read(TIMA_ADDR), 0x34);
+    }
+
+    #[test]
+    fn test_div_write_glitch_increments_tima() {
+        let mut timer = enabled_timer(0x05);
+        timer.tick(8);
+
+        timer.write(DIV_ADDR, 0x00);
+
+        assert_eq!(timer.read(TIMA_ADDR), 1);
+        assert_eq!(timer.read(DIV_ADDR), 0);
+    }
+
+    #[test]
+    fn test_tac_write_glitch_increments_tima() {
+        let mut timer = enabled_timer(0x05);
+        timer.tick(8);
+
+        timer.write(TAC_ADDR, 0x00);
+
+        assert_eq!(timer.read(TIMA_ADDR), 1);
+    }
+}
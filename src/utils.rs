@@ -5,10 +5,6 @@ pub fn split_hi_lo(value: u16) -> (u8, u8) {
     (hi, lo)
 }
 
-pub fn has_half_carry(a: u8, b: u8) -> bool {
-    ((a & 0x0F) + (b & 0x0F)) > 0x0F
-}
-
 #[cfg(test)]
 mod tests {
     use crate::utils::split_hi_lo;